@@ -15,16 +15,138 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use criterion::{criterion_group, criterion_main, Criterion};
-use digital_garden_visitor_counter::counter::render_separated_number;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use digital_garden_visitor_counter::counter::{
+    render_separated_number, render_separated_number_into, Frame, PngWriter, RenderBuffer,
+    RenderStyle,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
+/// Counts allocations so that the benchmarks can print how many each approach makes.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made by `f`.
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Numbers with 1, 5, 10, and 15 digits.
+const NUMBERS: [usize; 4] = [7, 12_345, 1_234_567_890, 123_456_789_012_345];
+
+/// Compares rendering into a new image every time with rendering into a reused buffer,
+/// and prints the allocations of each.
 pub fn render_bench(c: &mut Criterion) {
-    c.bench_function("render", |b| {
-        b.iter(|| {
-            let _render = render_separated_number(1_234_567_890, 10);
-        })
-    });
+    let style = RenderStyle::default();
+    let mut group = c.benchmark_group("render");
+    for number in NUMBERS {
+        let digits = number.to_string().len();
+        let mut buffer = RenderBuffer::new();
+        // Warm up the buffer so that only the steady state is counted.
+        render_separated_number_into(number, digits, &style, &mut buffer);
+        let new = count_allocations(|| {
+            render_separated_number(number, digits, &style);
+        });
+        let reused = count_allocations(|| {
+            render_separated_number_into(number, digits, &style, &mut buffer);
+        });
+        println!("{digits} digits: {new} allocations for a new render, {reused} when reused");
+
+        group.bench_with_input(BenchmarkId::new("new", digits), &number, |b, &number| {
+            b.iter(|| render_separated_number(number, digits, &style))
+        });
+        group.bench_with_input(BenchmarkId::new("reused", digits), &number, |b, &number| {
+            b.iter(|| {
+                render_separated_number_into(number, digits, &style, &mut buffer);
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Compares writing a PNG to a new buffer every time with writing it with a reused
+/// [`PngWriter`] and output buffer, and prints the allocations of each.
+pub fn png_writer_bench(c: &mut Criterion) {
+    let style = RenderStyle::default();
+    let mut group = c.benchmark_group("png");
+    for number in NUMBERS {
+        let digits = number.to_string().len();
+        let render = render_separated_number(number, digits, &style);
+        let mut writer = PngWriter::new();
+        let mut png = Vec::new();
+        let mut write = || {
+            png.clear();
+            writer.write(&render, &mut png).unwrap();
+        };
+        write();
+        let new = count_allocations(|| {
+            render.to_png_bytes().unwrap();
+        });
+        let reused = count_allocations(&mut write);
+        println!("{digits} digits: {new} allocations for a new PNG, {reused} when reused");
+
+        group.bench_function(BenchmarkId::new("new", digits), |b| {
+            b.iter(|| render.to_png_bytes().unwrap())
+        });
+        group.bench_function(BenchmarkId::new("reused", digits), |b| b.iter(&mut write));
+    }
+    group.finish();
+}
+
+/// Compares the encode time of RGBA and palette-indexed PNGs, and prints their sizes.
+pub fn encode_bench(c: &mut Criterion) {
+    let styles = [
+        ("plain", RenderStyle::default()),
+        (
+            "framed-3x",
+            RenderStyle {
+                frame: Frame::Framed,
+                scale: 3,
+                ..Default::default()
+            },
+        ),
+    ];
+    let mut group = c.benchmark_group("encode");
+    for (name, style) in &styles {
+        let render = render_separated_number(1_234_567_890, 10, style);
+        let rgba = render.to_rgba_png_bytes().unwrap().len();
+        let indexed = render.to_png_bytes().unwrap().len();
+        println!("{name}: RGBA PNG is {rgba} bytes, indexed PNG is {indexed} bytes");
+
+        group.bench_with_input(BenchmarkId::new("rgba", name), &render, |b, render| {
+            b.iter(|| render.to_rgba_png_bytes().unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("indexed", name), &render, |b, render| {
+            b.iter(|| render.to_png_bytes().unwrap())
+        });
+    }
+    group.finish();
 }
 
-criterion_group!(benches, render_bench);
+criterion_group!(benches, render_bench, png_writer_bench, encode_bench);
 criterion_main!(benches);
@@ -0,0 +1,72 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Checks a user agent and IP against the bot rules, the same way that the Lambda would.
+//!
+//! Usage: `check-bot [--rules <rules>] <user-agent> [ip]`
+//!
+//! The rules default to the `DGVC_BOT_RULES` environment variable. The exit code is 1 if the
+//! request would be rejected as a bot.
+
+use digital_garden_visitor_counter::bot_rules::BotRules;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: check-bot [--rules <rules>] <user-agent> [ip]";
+
+fn main() -> ExitCode {
+    let mut rules = std::env::var("DGVC_BOT_RULES").unwrap_or_default();
+    let mut positional = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" => match args.next() {
+                Some(value) => rules = value,
+                None => return usage(),
+            },
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let (user_agent, ip) = match positional.as_slice() {
+        [user_agent] => (user_agent, None),
+        [user_agent, ip] => (user_agent, Some(ip.as_str())),
+        _ => return usage(),
+    };
+
+    let rules: BotRules = match rules.parse() {
+        Ok(rules) => rules,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
+    let verdict = rules.check(user_agent, ip);
+    println!("{verdict}");
+    if verdict.is_bot() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{USAGE}");
+    ExitCode::from(2)
+}
@@ -75,14 +75,65 @@ export class InfrastructureStack extends Stack {
             description: "Minimum width of the counter in digits",
             default: "5",
         });
+        const counterOptionsParam = new CfnParameter(this, "counterOptions", {
+            type: "String",
+            description:
+                "Per-counter options in `name:option=value,option=value;name:...` format",
+            default: "",
+        });
+        const fontsParam = new CfnParameter(this, "fonts", {
+            type: "String",
+            description:
+                "Comma-separated list of BDF or PSF fonts to load in `name=path` format",
+            default: "",
+        });
+        const trustedProxiesParam = new CfnParameter(this, "trustedProxies", {
+            type: "String",
+            description:
+                "Comma-separated list of CIDR ranges of proxies that are trusted to forward the client's IP",
+            default: "",
+        });
+        const forwardingHeaderParam = new CfnParameter(this, "forwardingHeader", {
+            type: "String",
+            description:
+                "Header that trusted proxies write the client's IP to: `x-forwarded-for` or `forwarded`",
+            default: "x-forwarded-for",
+        });
+        const ipTruncationParam = new CfnParameter(this, "ipTruncation", {
+            type: "String",
+            description:
+                "IPv4 and IPv6 prefix lengths to truncate visitor IPs to before hashing them, such as `24,48`",
+            default: "",
+        });
+
+        const botRulesParam = new CfnParameter(this, "botRules", {
+            type: "String",
+            description:
+                "Semicolon-separated list of bot allow and deny rules, such as `deny-agent=linkcheck`",
+            default: "",
+        });
+
+        const botScoreThresholdParam = new CfnParameter(this, "botScoreThreshold", {
+            type: "String",
+            description:
+                "Header score at which requests are treated as bots, or empty to not score headers",
+            default: "",
+        });
 
         const counterLambda = new Function(this, "counter-lambda", {
             architecture: Architecture.ARM_64,
             code: Code.fromAsset("build/bootstrap/bootstrap.zip"),
             environment: {
                 DGVC_ALLOWED_NAMES: allowedNamesParam.valueAsString,
+                DGVC_BOT_RULES: botRulesParam.valueAsString,
+                DGVC_BOT_SCORE_THRESHOLD: botScoreThresholdParam.valueAsString,
+                DGVC_COUNTER_OPTIONS: counterOptionsParam.valueAsString,
+                DGVC_FONTS: fontsParam.valueAsString,
+                DGVC_FORWARDING_HEADER: forwardingHeaderParam.valueAsString,
+                DGVC_IP_TRUNCATION: ipTruncationParam.valueAsString,
                 DGVC_MIN_WIDTH: minWidthParam.valueAsString,
                 DGVC_TABLE_NAME: counterTable.tableName,
+                DGVC_TRUSTED_PROXIES: trustedProxiesParam.valueAsString,
                 RUST_BACKTRACE: "1",
             },
             functionName: "digital-garden-visitor-counter",
@@ -15,12 +15,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use digital_garden_visitor_counter::counter::render_separated_number;
+use digital_garden_visitor_counter::counter::{render_separated_number, RenderStyle};
 use minifb::{Key, Window, WindowOptions};
 use std::time::Duration;
 
 fn main() {
-    let render = render_separated_number(1, 10);
+    let style = RenderStyle::default();
+    let render = render_separated_number(1, 10, &style);
     let mut window = Window::new(
         "Test",
         render.width,
@@ -29,17 +30,18 @@ fn main() {
     )
     .unwrap();
 
-    // Render out to a file just to test PNG output.
+    // Render out to files just to test PNG and SVG output.
     {
-        let render = render_separated_number(1_234_567_890, 10);
+        let render = render_separated_number(1_234_567_890, 10, &style);
         std::fs::write("test-output.png", render.to_png_bytes().unwrap()).unwrap();
+        std::fs::write("test-output.svg", render.to_svg_string()).unwrap();
     }
 
     let mut num = 0;
 
     window.limit_update_rate(Some(Duration::from_millis(1000 / 60)));
     while window.is_open() && !window.is_key_down(Key::Escape) {
-        let render = render_separated_number(num, 10);
+        let render = render_separated_number(num, 10, &style);
         let pixels = &render.pixels;
         let mut buffer: Vec<u32> = vec![0; render.width * render.height];
         for (i, val) in buffer.iter_mut().enumerate() {
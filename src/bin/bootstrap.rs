@@ -16,12 +16,28 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use digital_garden_visitor_counter::{
-    counter::render_separated_number,
-    request_info::{RequestInfo, RequestInfoError},
-    store::{Store, Visitor},
+    bot_rules::BotRules,
+    counter::{
+        animation::render_odometer,
+        badge::{parse_badge_color, render_badge, BadgeStyle},
+        canvas::Direction,
+        font::{self, BitmapFont, LoadedFont},
+        metadata::PngMetadata,
+        render_composite, render_separated_number, DarkColors, RenderStyle, MAX_PRECISION,
+        MAX_SCALE,
+    },
+    image_format::ImageFormat,
+    render_cache::RenderCache,
+    request_info::{ForwardingHeader, RequestInfo, RequestInfoError, TrustedProxies},
+    store::{CountStats, IpTruncation, Store, Visitor},
 };
 use lambda_http::{run, service_fn, Body, Error, Request, RequestExt, Response};
-use std::{sync::Arc, time::SystemTime};
+use std::{
+    collections::HashMap, fmt, num::NonZeroUsize, str::FromStr, sync::Arc, time::SystemTime,
+};
+
+/// Number of rendered images to keep in memory between invocations.
+const RENDER_CACHE_CAPACITY: usize = 256;
 
 /// Configuration for the Lambda, set by environment variables.
 struct Config {
@@ -31,10 +47,30 @@ struct Config {
     min_width: usize,
     /// Allowed counter names, set by the `GHC_ALLOWED_NAMES` environment variable (comma-delimited).
     allowed_names: Vec<String>,
+    /// Fonts loaded from disk, set by the `DGVC_FONTS` environment variable.
+    fonts: Fonts,
+    /// Per-counter options, set by the `DGVC_COUNTER_OPTIONS` environment variable.
+    counter_options: HashMap<String, CounterOptions>,
+    /// Options for counters that don't have an entry in `counter_options`.
+    default_counter_options: CounterOptions,
+    /// Proxies that are trusted to forward the client's IP, set by the `DGVC_TRUSTED_PROXIES`
+    /// environment variable (comma-delimited CIDR ranges), and the header they write, set by
+    /// `DGVC_FORWARDING_HEADER`.
+    trusted_proxies: TrustedProxies,
+    /// How much of visitors' IPs to keep before hashing them, set by the `DGVC_IP_TRUNCATION`
+    /// environment variable (comma-delimited IPv4 and IPv6 prefix lengths).
+    ip_truncation: IpTruncation,
+    /// Bot allow and deny rules, set by the `DGVC_BOT_RULES` environment variable
+    /// (semicolon-delimited), and the header score threshold, set by `DGVC_BOT_SCORE_THRESHOLD`.
+    bot_rules: BotRules,
 }
 
 impl Config {
     fn from_env() -> Self {
+        let fonts = std::env::var("DGVC_FONTS")
+            .ok()
+            .map(|s| load_fonts(&s).unwrap())
+            .unwrap_or_default();
         Self {
             table_name: std::env::var("DGVC_TABLE_NAME")
                 .ok()
@@ -47,8 +83,227 @@ impl Config {
                 .ok()
                 .map(|s| s.split(',').map(String::from).collect())
                 .unwrap_or_else(|| vec!["default".into()]),
+            counter_options: std::env::var("DGVC_COUNTER_OPTIONS")
+                .ok()
+                .map(|s| CounterOptions::parse_all(&s, &fonts).unwrap())
+                .unwrap_or_default(),
+            default_counter_options: CounterOptions::default(),
+            trusted_proxies: std::env::var("DGVC_TRUSTED_PROXIES")
+                .ok()
+                .map(|s| s.parse::<TrustedProxies>().unwrap())
+                .unwrap_or_default()
+                .with_header(
+                    std::env::var("DGVC_FORWARDING_HEADER")
+                        .ok()
+                        .filter(|s| !s.is_empty())
+                        .map(|s| s.parse::<ForwardingHeader>().unwrap())
+                        .unwrap_or_default(),
+                ),
+            bot_rules: std::env::var("DGVC_BOT_RULES")
+                .ok()
+                .map(|s| s.parse::<BotRules>().unwrap())
+                .unwrap_or_default()
+                .with_score_threshold(
+                    std::env::var("DGVC_BOT_SCORE_THRESHOLD")
+                        .ok()
+                        .filter(|s| !s.is_empty())
+                        .map(|n| n.parse().unwrap()),
+                ),
+            ip_truncation: std::env::var("DGVC_IP_TRUNCATION")
+                .ok()
+                .map(|s| s.parse().unwrap())
+                .unwrap_or_default(),
+            fonts,
+        }
+    }
+
+    /// Returns the options for the counter with the given name.
+    fn counter_options(&self, name: &str) -> &CounterOptions {
+        self.counter_options
+            .get(name)
+            .unwrap_or(&self.default_counter_options)
+    }
+}
+
+/// Fonts loaded from disk by their name.
+type Fonts = HashMap<String, Arc<dyn BitmapFont>>;
+
+/// Load fonts from a comma-delimited list of `name=path` entries.
+fn load_fonts(value: &str) -> Result<Fonts, String> {
+    let mut fonts = Fonts::new();
+    for entry in value.split(',').filter(|entry| !entry.trim().is_empty()) {
+        let (name, path) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("font `{entry}` is missing a path"))?;
+        let (name, path) = (name.trim(), path.trim());
+        let font = LoadedFont::from_path(name, path)
+            .map_err(|err| format!("failed to load font `{name}` from `{path}`: {err}"))?;
+        fonts.insert(name.into(), Arc::new(font));
+    }
+    Ok(fonts)
+}
+
+/// Rendering options for a single counter.
+///
+/// These are configured with a semicolon-delimited list of `name:option=value,option=value`
+/// entries, such as `default:fg=000000,bg=ffffff;repo-readme:groups=ff0000|00ff00`.
+#[derive(Default)]
+struct CounterOptions {
+    style: RenderStyle,
+    /// Whether to roll the digits from the previous count to the new count when incremented.
+    odometer: bool,
+}
+
+impl CounterOptions {
+    /// Parse the options for every counter.
+    fn parse_all(value: &str, fonts: &Fonts) -> Result<HashMap<String, CounterOptions>, String> {
+        let mut all = HashMap::new();
+        for entry in value.split(';').filter(|entry| !entry.trim().is_empty()) {
+            let (name, options) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("counter options `{entry}` are missing a name"))?;
+            let counter: &mut CounterOptions = all.entry(name.trim().into()).or_default();
+            for option in options
+                .split(',')
+                .filter(|option| !option.trim().is_empty())
+            {
+                let (key, value) = option
+                    .split_once('=')
+                    .ok_or_else(|| format!("counter option `{option}` is missing a value"))?;
+                counter.set(key.trim(), value.trim(), fonts)?;
+            }
+        }
+        Ok(all)
+    }
+
+    /// Set a single option by its key.
+    fn set(&mut self, key: &str, value: &str, fonts: &Fonts) -> Result<(), String> {
+        match key {
+            // Digit color
+            "fg" => self.style.foreground = parse_option(value)?,
+            // Background color
+            "bg" => self.style.background = parse_option(value)?,
+            // Pipe-delimited digit colors per group, starting from the rightmost group
+            "groups" => {
+                self.style.group_colors = value
+                    .split('|')
+                    .map(parse_option)
+                    .collect::<Result<_, _>>()?
+            }
+            // Name of the font
+            "font" => self.style.font = parse_font(value, fonts)?,
+            // Default integer scale factor
+            "scale" => self.style.scale = parse_scale(value)?,
+            // Digit grouping and separator
+            "locale" => self.style.locale = parse_option(value)?,
+            // What to draw in the reserved digits
+            "pad" => self.style.padding = parse_option(value)?,
+            // Abbreviation of large numbers, and how many digits to keep after the decimal point
+            "notation" => self.style.notation = parse_option(value)?,
+            "precision" => self.style.precision = parse_precision(value)?,
+            // Boxes around the digits, and their colors
+            "frame" => self.style.frame = parse_option(value)?,
+            "cell" => self.style.cell_color = parse_option(value)?,
+            "frame-color" => self.style.frame_color = parse_option(value)?,
+            // Colors for readers that prefer a dark color scheme
+            "dark-fg" => self.dark_colors().foreground = Some(parse_option(value)?),
+            "dark-bg" => self.dark_colors().background = Some(parse_option(value)?),
+            "dark-groups" => {
+                self.dark_colors().group_colors = Some(
+                    value
+                        .split('|')
+                        .map(parse_option)
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            "dark-cell" => self.dark_colors().cell_color = Some(parse_option(value)?),
+            "dark-frame-color" => self.dark_colors().frame_color = Some(parse_option(value)?),
+            // Labels on either side of the number
+            "prefix" => self.style.prefix = parse_label(value)?,
+            "suffix" => self.style.suffix = parse_label(value)?,
+            // Animation to use when the count is incremented
+            "animate" => {
+                self.odometer = match value {
+                    "odometer" => true,
+                    "none" => false,
+                    _ => return Err(format!("unknown animation `{value}`")),
+                }
+            }
+            _ => return Err(format!("unknown counter option `{key}`")),
+        }
+        Ok(())
+    }
+
+    fn dark_colors(&mut self) -> &mut DarkColors {
+        self.style.dark_colors.get_or_insert_with(Default::default)
+    }
+}
+
+/// Look up a font by name, preferring loaded fonts over the built-in ones.
+fn parse_font(value: &str, fonts: &Fonts) -> Result<Arc<dyn BitmapFont>, String> {
+    fonts
+        .get(value)
+        .cloned()
+        .or_else(|| font::builtin(value))
+        .ok_or_else(|| {
+            let mut names: Vec<&str> = fonts.keys().map(String::as_str).collect();
+            names.extend(font::BUILTIN_FONT_NAMES);
+            format!(
+                "unknown font `{value}` (expected one of: {})",
+                names.join(", ")
+            )
+        })
+}
+
+/// Parse an integer scale factor, rejecting anything outside of 1 through `MAX_SCALE`.
+fn parse_scale(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(scale) if (1..=MAX_SCALE).contains(&scale) => Ok(scale),
+        _ => Err(format!(
+            "invalid scale `{value}` (expected a number from 1 to {MAX_SCALE})"
+        )),
+    }
+}
+
+/// Parse the number of digits after the decimal point, rejecting anything over `MAX_PRECISION`.
+fn parse_precision(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(precision) if precision <= MAX_PRECISION => Ok(precision),
+        _ => Err(format!(
+            "invalid precision `{value}` (expected a number from 0 to {MAX_PRECISION})"
+        )),
+    }
+}
+
+/// Parse a label, which is percent-encoded so that it can contain spaces and the characters
+/// that delimit the options, such as `Visitors:%20`.
+fn parse_label(value: &str) -> Result<String, String> {
+    let mut label = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next(), bytes.next()];
+            let decoded = match hex {
+                [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                _ => None,
+            };
+            label.push(decoded.ok_or_else(|| format!("invalid percent-encoding in `{value}`"))?);
+        } else {
+            label.push(byte);
         }
     }
+    String::from_utf8(label).map_err(|_| format!("label `{value}` isn't valid UTF-8"))
+}
+
+/// Parse a single option value, converting the error into a message.
+fn parse_option<T>(value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|err: T::Err| err.to_string())
 }
 
 fn not_found() -> Response<Body> {
@@ -58,32 +313,279 @@ fn not_found() -> Response<Body> {
         .expect("valid response")
 }
 
+fn bad_request() -> Response<Body> {
+    Response::builder()
+        .status(400)
+        .body(Body::Empty)
+        .expect("valid response")
+}
+
+/// Escape a string for inclusion in a JSON document, including the surrounding quotes.
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
 async fn function_handler(
+    config: Arc<Config>,
+    store: Arc<Store>,
+    cache: Arc<RenderCache>,
+    event: Request,
+) -> Result<Response<Body>, Error> {
+    // Don't respond to unknown paths, such as `/favicon.ico`.
+    match event.uri().path() {
+        "/" | "/badge.svg" => image_handler(config, store, cache, event).await,
+        "/count.json" => count_handler(config, store, event, true).await,
+        "/count.txt" => count_handler(config, store, event, false).await,
+        _ => Ok(not_found()),
+    }
+}
+
+/// Respond with the count as JSON or plain text, without recording a visit.
+///
+/// These are meant for scripts and static site generators rather than visitors, so bots aren't
+/// rejected, and CORS is allowed from any origin so that widgets on other sites can fetch them.
+async fn count_handler(
     config: Arc<Config>,
     store: Arc<Store>,
     event: Request,
+    json: bool,
 ) -> Result<Response<Body>, Error> {
-    // Don't respond to non-root requests, such as `/favicon.ico`.
-    if event.uri().path() != "/" {
+    let count_name = event
+        .query_string_parameters_ref()
+        .and_then(|params| params.first("name"))
+        .unwrap_or("default");
+
+    // Security: Reject any names that are not allow listed.
+    if !config.allowed_names.iter().any(|name| name == count_name) {
         return Ok(not_found());
     }
 
-    // Extract some information from the request.
-    let request_info = match RequestInfo::try_from(&event) {
-        Ok(info) => info,
-        // Quickly reject bots to avoid inflating the counter and reduce costs.
-        Err(RequestInfoError::LooksLikeABot) => {
-            return Ok(not_found());
+    let CountStats {
+        count,
+        recent,
+        online,
+    } = store.count_stats(count_name, SystemTime::now()).await?;
+    let (content_type, body) = if json {
+        (
+            "application/json",
+            format!(
+                "{{\"name\":{},\"count\":{count},\"recent\":{recent},\"online\":{online}}}",
+                json_string(count_name)
+            ),
+        )
+    } else {
+        ("text/plain; charset=utf-8", format!("{count}\n"))
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("cache-control", "no-cache")
+        .header("content-type", content_type)
+        .header("access-control-allow-origin", "*")
+        .body(Body::Text(body))
+        .expect("valid response"))
+}
+
+/// What to respond to a visit with.
+enum Output {
+    /// A counter image in the given format.
+    Image {
+        style: RenderStyle,
+        format: ImageFormat,
+        /// Whether to describe PNG images with metadata chunks.
+        metadata: bool,
+    },
+    /// An image with several labeled figures in the given format.
+    Composite {
+        style: RenderStyle,
+        format: ImageFormat,
+        metrics: Vec<Metric>,
+        direction: Direction,
+    },
+    /// A shields.io-style SVG badge.
+    Badge(BadgeStyle),
+}
+
+/// Work out the counter image's style and format from the counter's options and the request.
+fn image_output(config: &Config, count_name: &str, event: &Request) -> Result<Output, String> {
+    let params = event.query_string_parameters_ref();
+
+    // Allow the scale to be overridden so that `srcset` variants can share a counter,
+    // and the font so that different pages can give the same counter a different look.
+    let mut style = config.counter_options(count_name).style.clone();
+    if let Some(scale) = params.and_then(|params| params.first("scale")) {
+        style.scale = parse_scale(scale)?;
+    }
+    if let Some(font) = params.and_then(|params| params.first("font")) {
+        style.font = parse_font(font, &config.fonts)?;
+    }
+
+    // Pick the image format, which can be forced with the `format` query parameter
+    // for clients that can't send an `Accept` header, such as Markdown image links.
+    let format = match params.and_then(|params| params.first("format")) {
+        Some(format) => parse_option(format)?,
+        None => ImageFormat::negotiate(
+            event
+                .headers()
+                .get("accept")
+                .and_then(|accept| accept.to_str().ok()),
+        ),
+    };
+
+    // Show several figures in one image if they are asked for with `metrics=total,online`.
+    let metrics = match params.and_then(|params| params.first("metrics")) {
+        Some(metrics) => metrics
+            .split(',')
+            .map(parse_option)
+            .collect::<Result<Vec<Metric>, _>>()?,
+        None => {
+            // Archived PNGs can describe themselves with `metadata=true`.
+            let metadata = params
+                .and_then(|params| params.first("metadata"))
+                .map(parse_option)
+                .transpose()?
+                .unwrap_or(false);
+            return Ok(Output::Image {
+                style,
+                format,
+                metadata,
+            });
         }
-        Err(err) => return Err(err.into()),
     };
+    if metrics.len() > MAX_METRICS {
+        return Err(format!("more than {MAX_METRICS} metrics"));
+    }
+    let direction = params
+        .and_then(|params| params.first("layout"))
+        .map(parse_option)
+        .transpose()?
+        .unwrap_or_default();
+    Ok(Output::Composite {
+        style,
+        format,
+        metrics,
+        direction,
+    })
+}
+
+/// The most figures allowed in a composite image, which is enough to show each of them once.
+const MAX_METRICS: usize = 3;
+
+/// A figure that can be shown in a composite image.
+#[derive(Copy, Clone)]
+enum Metric {
+    /// The count.
+    Total,
+    /// Unique visitors in the last two hours.
+    Recent,
+    /// Unique visitors in the last five minutes.
+    Online,
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "total" => Ok(Metric::Total),
+            "recent" => Ok(Metric::Recent),
+            "online" => Ok(Metric::Online),
+            _ => Err(format!(
+                "unknown metric `{value}` (expected one of: total, recent, online)"
+            )),
+        }
+    }
+}
+
+impl Metric {
+    fn label(&self) -> &'static str {
+        match self {
+            Metric::Total => "Total",
+            Metric::Recent => "Recent",
+            Metric::Online => "Online",
+        }
+    }
+
+    fn value(&self, stats: &CountStats) -> usize {
+        match self {
+            Metric::Total => stats.count,
+            Metric::Recent => stats.recent,
+            Metric::Online => stats.online,
+        }
+    }
+}
+
+/// The longest badge label allowed, in characters.
+const MAX_BADGE_LABEL_LEN: usize = 64;
+
+/// Work out the badge's style from the request.
+fn badge_output(event: &Request) -> Result<Output, String> {
+    let params = event.query_string_parameters_ref();
+    let param = |name| params.and_then(|params| params.first(name));
+
+    let mut style = BadgeStyle::default();
+    if let Some(label) = param("label") {
+        if label.chars().count() > MAX_BADGE_LABEL_LEN {
+            return Err(format!(
+                "badge label is longer than {MAX_BADGE_LABEL_LEN} characters"
+            ));
+        }
+        if label.chars().any(char::is_control) {
+            return Err("badge label can't have control characters".into());
+        }
+        style.label = label.into();
+    }
+    if let Some(color) = param("color") {
+        style.color = parse_badge_color(color).map_err(|err| err.to_string())?;
+    }
+    if let Some(color) = param("label-color") {
+        style.label_color = parse_badge_color(color).map_err(|err| err.to_string())?;
+    }
+    if let Some(shape) = param("style") {
+        style.shape = parse_option(shape)?;
+    }
+    Ok(Output::Badge(style))
+}
+
+/// Respond with an image of the count after recording the visit.
+async fn image_handler(
+    config: Arc<Config>,
+    store: Arc<Store>,
+    cache: Arc<RenderCache>,
+    event: Request,
+) -> Result<Response<Body>, Error> {
+    // Extract some information from the request.
+    let request_info =
+        match RequestInfo::from_request(&event, &config.trusted_proxies, &config.bot_rules) {
+            Ok(info) => info,
+            // Quickly reject bots to avoid inflating the counter and reduce costs.
+            Err(RequestInfoError::LooksLikeABot) => {
+                return Ok(not_found());
+            }
+            Err(err @ RequestInfoError::SuspiciousHeaders(_)) => {
+                tracing::info!("rejected a request: {err}");
+                return Ok(not_found());
+            }
+            Err(err) => return Err(err.into()),
+        };
 
     // Create a semi-unique hash of the visitor's IP and user agent.
-    let visitor = Visitor::from(&request_info);
+    let visitor = Visitor::from_request_info(&request_info, config.ip_truncation);
 
     // Get the name of the counter to increment from query parameters.
-    let count_name = event
-        .query_string_parameters_ref()
+    let params = event.query_string_parameters_ref();
+    let count_name = params
         .and_then(|params| params.first("name"))
         .unwrap_or("default");
 
@@ -92,25 +594,111 @@ async fn function_handler(
         return Ok(not_found());
     }
 
+    // Work out what to respond with before recording the visit so that
+    // invalid requests don't increment the counter.
+    let output = match event.uri().path() {
+        "/badge.svg" => badge_output(&event),
+        _ => image_output(&config, count_name, &event),
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(_) => return Ok(bad_request()),
+    };
+
     // Privacy: This only temporarily stores a 32-bit hash of the visitor's IP and user agent
     // so that we can roughly track uniqueness without storing any identifying information.
-    let count = store
+    let visit = store
         .maybe_increment_visitors(visitor, count_name, SystemTime::now())
         .await?;
+    let count = visit.count;
 
-    // Render the counter to an in-memory PNG.
-    let render = render_separated_number(count, config.min_width);
-    let png_bytes = render.to_png_bytes()?;
+    // Whether the image came from the render cache, for images that can be cached.
+    let mut cache_hit = None;
+    let (content_type, image_bytes) = match output {
+        // Render the counter to an in-memory image. PNGs are animated if the count was incremented
+        // and the counter has the odometer animation enabled, while other formats show the new count.
+        Output::Image {
+            style,
+            format,
+            metadata,
+        } => {
+            let image_bytes = if format == ImageFormat::Png && metadata {
+                // PNGs with metadata are always still images, and aren't cached since they
+                // include the time that they were generated.
+                let metadata = PngMetadata {
+                    name: Some(count_name.into()),
+                    count: Some(count),
+                    created: Some(SystemTime::now()),
+                    srgb: true,
+                };
+                render_separated_number(count, config.min_width, &style)
+                    .to_png_bytes_with_metadata(&metadata)?
+            } else if format == ImageFormat::Png
+                && config.counter_options(count_name).odometer
+                && visit.previous_count != count
+            {
+                render_odometer(visit.previous_count, count, config.min_width, &style)
+                    .to_apng_bytes()?
+            } else {
+                let image = cache.get_or_render(count, config.min_width, &style, format)?;
+                if !image.hit {
+                    let stats = cache.stats();
+                    tracing::info!(
+                        hits = stats.hits,
+                        misses = stats.misses,
+                        "rendered a counter that wasn't cached"
+                    );
+                }
+                cache_hit = Some(image.hit);
+                image.bytes.to_vec()
+            };
+            (format.content_type(), image_bytes)
+        }
+        Output::Composite {
+            style,
+            format,
+            metrics,
+            direction,
+        } => {
+            // Use the count that was just recorded, since another visit could've been recorded since.
+            let stats = CountStats {
+                count,
+                ..store.count_stats(count_name, SystemTime::now()).await?
+            };
+            let numbers: Vec<(&str, usize)> = metrics
+                .iter()
+                .map(|metric| (metric.label(), metric.value(&stats)))
+                .collect();
+            // Reserving digits lines the numbers up in a column, but only spaces them out in a row.
+            let reserve_width = match direction {
+                Direction::Row => 0,
+                Direction::Column => config.min_width,
+            };
+            let image_bytes = format.render_and_encode(&style, |style| {
+                render_composite(&numbers, reserve_width, style, direction)
+            })?;
+            (format.content_type(), image_bytes)
+        }
+        Output::Badge(style) => (
+            ImageFormat::Svg.content_type(),
+            render_badge(count, &style).into_bytes(),
+        ),
+    };
 
-    Ok(Response::builder()
+    let mut response = Response::builder()
         .status(200)
         .header("cache-control", "no-cache")
-        .header("content-type", "image/png")
-        .header("content-length", png_bytes.len())
+        .header("content-type", content_type)
+        .header("vary", "accept")
+        .header("content-length", image_bytes.len())
         .header("x-count-name", count_name)
         .header("x-count", count)
-        .header("x-tag", visitor.tag)
-        .body(Body::Binary(png_bytes))
+        .header("x-tag", visitor.tag);
+    if let Some(hit) = cache_hit {
+        response = response.header("x-render-cache", if hit { "hit" } else { "miss" });
+    }
+    Ok(response
+        .body(Body::Binary(image_bytes))
         .expect("valid response"))
 }
 
@@ -124,9 +712,13 @@ async fn main() -> Result<(), Error> {
 
     let config = Arc::new(Config::from_env());
     let store = Arc::new(Store::new(config.table_name.clone()).await);
+    // The cache outlives invocations since the Lambda runtime reuses warm instances.
+    let cache = Arc::new(RenderCache::new(
+        NonZeroUsize::new(RENDER_CACHE_CAPACITY).expect("nonzero"),
+    ));
 
     run(service_fn(move |event| {
-        function_handler(config.clone(), store.clone(), event)
+        function_handler(config.clone(), store.clone(), cache.clone(), event)
     }))
     .await
 }
@@ -0,0 +1,356 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bot filtering with the [`isbot`] default list, and rules supplied by the operator.
+//!
+//! Rules are a semicolon-delimited list of `kind=pattern` entries, where the kind is one of:
+//!
+//! - `deny-agent` and `allow-agent` for a case-insensitive substring of the user agent
+//! - `deny-agent-regex` and `allow-agent-regex` for a case-insensitive regular expression
+//!   that is searched for in the user agent
+//! - `deny-ip` and `allow-ip` for an IP address or CIDR range that the visitor's IP is in
+//!
+//! Deny rules add to the default list, and allow rules take precedence over both, so that
+//! a visitor that the default list would reject can be let through. Allow rules also
+//! exempt visitors from header scoring, which is enabled with a [`BotScore`] threshold.
+
+use crate::{
+    bot_score::BotScore,
+    request_info::{IpRange, ParseIpRangeError},
+};
+use isbot::Bots;
+use lambda_http::http::HeaderMap;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use std::{error::Error as StdError, fmt, net::IpAddr, str::FromStr};
+
+/// Initialize the bot checker once and reuse it for every request.
+static BOT_CHECKER: Lazy<Bots> = Lazy::new(Bots::default);
+
+/// What happens to a request that matches a [`BotRule`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BotAction {
+    /// Never treat the request as a bot.
+    Allow,
+    /// Treat the request as a bot.
+    Deny,
+}
+
+/// What part of a request a [`BotRule`] matches.
+#[derive(Clone, Debug)]
+pub enum BotMatcher {
+    /// A lowercase substring of the user agent.
+    Agent(String),
+    /// A case-insensitive regular expression that is searched for in the user agent.
+    AgentRegex(Regex),
+    /// A range of source IPs.
+    Ip(IpRange),
+}
+
+impl BotMatcher {
+    fn matches(&self, user_agent: &str, source_ip: Option<IpAddr>) -> bool {
+        match self {
+            Self::Agent(substring) => user_agent.to_ascii_lowercase().contains(substring.as_str()),
+            Self::AgentRegex(regex) => regex.is_match(user_agent),
+            Self::Ip(range) => source_ip.map(|ip| range.contains(ip)).unwrap_or(false),
+        }
+    }
+}
+
+/// A single allow or deny rule.
+#[derive(Clone, Debug)]
+pub struct BotRule {
+    pub action: BotAction,
+    pub matcher: BotMatcher,
+}
+
+impl fmt::Display for BotRule {
+    /// Formats the rule the same way that it's parsed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.action {
+            BotAction::Allow => "allow",
+            BotAction::Deny => "deny",
+        };
+        match &self.matcher {
+            BotMatcher::Agent(substring) => write!(f, "{action}-agent={substring}"),
+            BotMatcher::AgentRegex(regex) => write!(f, "{action}-agent-regex={}", regex.as_str()),
+            BotMatcher::Ip(range) => write!(f, "{action}-ip={range}"),
+        }
+    }
+}
+
+/// An error parsing a [`BotRule`] or [`BotRules`] from a string.
+#[derive(Debug)]
+pub enum ParseBotRuleError {
+    /// The rule isn't in `kind=pattern` format, or has an unknown kind.
+    InvalidRule(String),
+    /// The rule has an empty pattern, which would match everything.
+    EmptyPattern(String),
+    /// The regular expression of an `agent-regex` rule is invalid.
+    InvalidRegex(regex::Error),
+    /// The IP range of an `ip` rule is invalid.
+    InvalidIpRange(ParseIpRangeError),
+}
+
+impl StdError for ParseBotRuleError {}
+
+impl fmt::Display for ParseBotRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRule(rule) => write!(
+                f,
+                "invalid bot rule `{rule}` (expected `allow` or `deny` followed by \
+                 `-agent`, `-agent-regex`, or `-ip`, and then `=pattern`)"
+            ),
+            Self::EmptyPattern(rule) => write!(f, "bot rule `{rule}` has an empty pattern"),
+            Self::InvalidRegex(err) => write!(f, "invalid bot rule regex: {err}"),
+            Self::InvalidIpRange(err) => write!(f, "invalid bot rule: {err}"),
+        }
+    }
+}
+
+impl FromStr for BotRule {
+    type Err = ParseBotRuleError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseBotRuleError::InvalidRule(value.into());
+        let (kind, pattern) = value.split_once('=').ok_or_else(invalid)?;
+        let (action, kind) = match kind.trim().split_once('-').ok_or_else(invalid)? {
+            ("allow", kind) => (BotAction::Allow, kind),
+            ("deny", kind) => (BotAction::Deny, kind),
+            _ => return Err(invalid()),
+        };
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(ParseBotRuleError::EmptyPattern(value.into()));
+        }
+        let matcher = match kind {
+            "agent" => BotMatcher::Agent(pattern.to_ascii_lowercase()),
+            "agent-regex" => BotMatcher::AgentRegex(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(ParseBotRuleError::InvalidRegex)?,
+            ),
+            "ip" => BotMatcher::Ip(pattern.parse().map_err(ParseBotRuleError::InvalidIpRange)?),
+            _ => return Err(invalid()),
+        };
+        Ok(BotRule { action, matcher })
+    }
+}
+
+/// Why a request was or wasn't considered a bot by [`BotRules::check`].
+#[derive(Copy, Clone, Debug)]
+pub enum BotVerdict<'a> {
+    /// An allow rule matched, so the request isn't a bot.
+    Allowed(&'a BotRule),
+    /// A deny rule matched, so the request is a bot.
+    Denied(&'a BotRule),
+    /// The user agent is on the default bot list.
+    KnownBot,
+    /// Nothing matched, so the request isn't a bot.
+    NotABot,
+}
+
+impl BotVerdict<'_> {
+    /// Returns true if the request should be rejected as a bot.
+    pub fn is_bot(&self) -> bool {
+        matches!(self, Self::Denied(_) | Self::KnownBot)
+    }
+}
+
+impl fmt::Display for BotVerdict<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allowed(rule) => write!(f, "not a bot (allowed by `{rule}`)"),
+            Self::Denied(rule) => write!(f, "bot (denied by `{rule}`)"),
+            Self::KnownBot => f.write_str("bot (on the default bot list)"),
+            Self::NotABot => f.write_str("not a bot"),
+        }
+    }
+}
+
+/// The operator's allow and deny rules, which are checked along with the default bot list.
+#[derive(Clone, Debug, Default)]
+pub struct BotRules {
+    rules: Vec<BotRule>,
+    score_threshold: Option<u32>,
+}
+
+impl FromStr for BotRules {
+    type Err = ParseBotRuleError;
+
+    /// Parse a semicolon-delimited list of rules.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .split(';')
+            .filter(|rule| !rule.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(|rules| BotRules {
+                rules,
+                score_threshold: None,
+            })
+    }
+}
+
+impl BotRules {
+    /// Returns the rules in the order they were given.
+    pub fn rules(&self) -> &[BotRule] {
+        &self.rules
+    }
+
+    /// Treat requests whose headers have a [`BotScore`] of at least `threshold` as bots,
+    /// or don't score headers if it's `None`, which is the default.
+    pub fn with_score_threshold(mut self, threshold: Option<u32>) -> Self {
+        self.score_threshold = threshold;
+        self
+    }
+
+    /// Score the request's headers if header scoring is enabled, returning the score
+    /// if it reaches the threshold.
+    pub fn suspicious_headers(&self, headers: &HeaderMap, user_agent: &str) -> Option<BotScore> {
+        let threshold = self.score_threshold?;
+        let score = BotScore::from_headers(headers, user_agent);
+        (score.total() >= threshold).then_some(score)
+    }
+
+    /// Check a request's user agent and source IP against the rules and the default bot list.
+    ///
+    /// Allow rules are checked first, then deny rules, and then the default list. A source IP
+    /// that can't be parsed doesn't match any IP rules.
+    pub fn check(&self, user_agent: &str, source_ip: Option<&str>) -> BotVerdict<'_> {
+        let source_ip = source_ip.and_then(|ip| ip.parse().ok());
+        let matching = |action| {
+            self.rules
+                .iter()
+                .find(|rule| rule.action == action && rule.matcher.matches(user_agent, source_ip))
+        };
+        if let Some(rule) = matching(BotAction::Allow) {
+            BotVerdict::Allowed(rule)
+        } else if let Some(rule) = matching(BotAction::Deny) {
+            BotVerdict::Denied(rule)
+        } else if BOT_CHECKER.is_bot(user_agent) {
+            BotVerdict::KnownBot
+        } else {
+            BotVerdict::NotABot
+        }
+    }
+
+    /// Returns true if the request should be rejected as a bot.
+    pub fn is_bot(&self, user_agent: &str, source_ip: Option<&str>) -> bool {
+        self.check(user_agent, source_ip).is_bot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROWSER: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/118.0";
+
+    fn verdict(rules: &str, user_agent: &str, source_ip: &str) -> String {
+        let rules: BotRules = rules.parse().unwrap();
+        rules.check(user_agent, Some(source_ip)).to_string()
+    }
+
+    #[test]
+    fn default_list_without_rules() {
+        let rules = BotRules::default();
+        assert!(rules.is_bot("Googlebot/2.1 (+http://www.google.com/bot.html)", None));
+        assert!(!rules.is_bot(BROWSER, Some("127.0.0.1")));
+    }
+
+    #[test]
+    fn deny_rules() {
+        let rules =
+            "deny-agent=LinkChecker; deny-agent-regex=^acme-(probe|watch)/\\d; deny-ip=198.51.100.0/24";
+        assert_eq!(
+            "bot (denied by `deny-agent=linkchecker`)",
+            verdict(rules, "Our-linkchecker/1.0", "127.0.0.1")
+        );
+        assert_eq!(
+            "bot (denied by `deny-agent-regex=^acme-(probe|watch)/\\d`)",
+            verdict(rules, "Acme-Probe/1.23", "127.0.0.1")
+        );
+        assert_eq!(
+            "not a bot",
+            verdict(rules, "Not Acme-Probe/1.23", "127.0.0.1")
+        );
+        assert_eq!(
+            "bot (denied by `deny-ip=198.51.100.0/24`)",
+            verdict(rules, BROWSER, "198.51.100.42")
+        );
+        assert_eq!("not a bot", verdict(rules, BROWSER, "198.51.101.42"));
+        assert!(!rules
+            .parse::<BotRules>()
+            .unwrap()
+            .is_bot(BROWSER, Some("not an ip")));
+    }
+
+    #[test]
+    fn allow_rules_take_precedence() {
+        let rules = "deny-ip=10.0.0.0/8; allow-ip=10.1.2.3; allow-agent=Googlebot";
+        assert_eq!(
+            "not a bot (allowed by `allow-ip=10.1.2.3/32`)",
+            verdict(rules, BROWSER, "10.1.2.3")
+        );
+        assert_eq!(
+            "bot (denied by `deny-ip=10.0.0.0/8`)",
+            verdict(rules, BROWSER, "10.1.2.4")
+        );
+        assert_eq!(
+            "not a bot (allowed by `allow-agent=googlebot`)",
+            verdict(rules, "Googlebot/2.1", "127.0.0.1")
+        );
+        assert_eq!(
+            "bot (on the default bot list)",
+            verdict(rules, "bingbot/2.0", "127.0.0.1")
+        );
+    }
+
+    #[test]
+    fn parse_rules() {
+        let rules: BotRules = " deny-agent=a ;; allow-ip=::1 ;".parse().unwrap();
+        let rules: Vec<_> = rules.rules().iter().map(ToString::to_string).collect();
+        assert_eq!(vec!["deny-agent=a", "allow-ip=::1/128"], rules);
+
+        for invalid in [
+            "deny",
+            "deny-agent",
+            "block-agent=a",
+            "deny-host=a",
+            "allow-agent=",
+        ] {
+            assert!(
+                matches!(
+                    invalid.parse::<BotRules>(),
+                    Err(ParseBotRuleError::InvalidRule(_) | ParseBotRuleError::EmptyPattern(_))
+                ),
+                "{invalid}"
+            );
+        }
+        assert!(matches!(
+            "deny-agent-regex=(".parse::<BotRules>(),
+            Err(ParseBotRuleError::InvalidRegex(_))
+        ));
+        assert!(matches!(
+            "deny-ip=10.0.0.0/33".parse::<BotRules>(),
+            Err(ParseBotRuleError::InvalidIpRange(_))
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Heuristic bot scoring based on the headers that browsers send with image requests.
+//!
+//! Headless browsers and scrapers can send a browser's user agent, but they tend to leave
+//! out or get wrong the other headers that real browsers send. Each suspicious header adds
+//! to a score, and requests that reach a configured threshold are treated as bots.
+
+use lambda_http::http::HeaderMap;
+use std::fmt;
+
+/// A suspicious thing about a request's headers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BotSignal {
+    /// There is no `Accept-Language` header, which browsers always send.
+    MissingAcceptLanguage,
+    /// There is no `Accept-Encoding` header, which browsers always send.
+    MissingAcceptEncoding,
+    /// There is no `Accept` header, which browsers always send.
+    MissingAccept,
+    /// The `Accept` header doesn't allow any image types, not even with `image/*` or `*/*`.
+    AcceptWithoutImages,
+    /// The user agent claims to be Chrome or Firefox, which send `Sec-Fetch-*` headers,
+    /// but there aren't any.
+    MissingFetchMetadata,
+    /// `Sec-Fetch-Dest` says the response isn't going to be used as an image or a page.
+    UnexpectedFetchDest(String),
+    /// There is a `Sec-CH-UA` header, which only Chromium-based browsers send,
+    /// but the user agent isn't Chromium-based.
+    ClientHintsMismatch,
+    /// `Sec-CH-UA` says that the browser is headless.
+    HeadlessClientHints,
+}
+
+impl BotSignal {
+    /// How much this signal adds to a request's score.
+    pub fn weight(&self) -> u32 {
+        match self {
+            Self::MissingAcceptEncoding
+            | Self::MissingAccept
+            | Self::AcceptWithoutImages
+            | Self::UnexpectedFetchDest(_) => 1,
+            Self::MissingAcceptLanguage | Self::MissingFetchMetadata => 2,
+            Self::ClientHintsMismatch | Self::HeadlessClientHints => 3,
+        }
+    }
+}
+
+impl fmt::Display for BotSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAcceptLanguage => f.write_str("no Accept-Language header"),
+            Self::MissingAcceptEncoding => f.write_str("no Accept-Encoding header"),
+            Self::MissingAccept => f.write_str("no Accept header"),
+            Self::AcceptWithoutImages => f.write_str("Accept header doesn't allow images"),
+            Self::MissingFetchMetadata => {
+                f.write_str("browser user agent without Sec-Fetch headers")
+            }
+            Self::UnexpectedFetchDest(dest) => write!(f, "unexpected Sec-Fetch-Dest `{dest}`"),
+            Self::ClientHintsMismatch => {
+                f.write_str("Sec-CH-UA header doesn't match the user agent")
+            }
+            Self::HeadlessClientHints => f.write_str("Sec-CH-UA header says it's headless"),
+        }
+    }
+}
+
+/// The suspicious signals found in a request's headers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BotScore {
+    signals: Vec<BotSignal>,
+}
+
+impl BotScore {
+    /// Score a request from its headers and user agent.
+    pub fn from_headers(headers: &HeaderMap, user_agent: &str) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let chromium = user_agent.contains("Chrome/") || user_agent.contains("Chromium/");
+        let mut signals = Vec::new();
+
+        if header("accept-language").is_none() {
+            signals.push(BotSignal::MissingAcceptLanguage);
+        }
+        if header("accept-encoding").is_none() {
+            signals.push(BotSignal::MissingAcceptEncoding);
+        }
+        match header("accept") {
+            Some(accept) if !accepts_images(accept) => signals.push(BotSignal::AcceptWithoutImages),
+            Some(_) => {}
+            None => signals.push(BotSignal::MissingAccept),
+        }
+
+        match header("sec-fetch-dest") {
+            Some("image" | "document") => {}
+            Some(dest) => signals.push(BotSignal::UnexpectedFetchDest(dest.into())),
+            None => {
+                let fetch_metadata = ["sec-fetch-mode", "sec-fetch-site"]
+                    .iter()
+                    .any(|name| headers.contains_key(*name));
+                if !fetch_metadata && (chromium || user_agent.contains("Firefox/")) {
+                    signals.push(BotSignal::MissingFetchMetadata);
+                }
+            }
+        }
+
+        if let Some(brands) = header("sec-ch-ua") {
+            if !chromium {
+                signals.push(BotSignal::ClientHintsMismatch);
+            }
+            if brands.contains("Headless") {
+                signals.push(BotSignal::HeadlessClientHints);
+            }
+        }
+        BotScore { signals }
+    }
+
+    /// Returns the signals that were found.
+    pub fn signals(&self) -> &[BotSignal] {
+        &self.signals
+    }
+
+    /// Returns the sum of the signals' weights.
+    pub fn total(&self) -> u32 {
+        self.signals.iter().map(BotSignal::weight).sum()
+    }
+}
+
+/// Returns true if any media range of an `Accept` header, such as `image/webp`, `image/*`,
+/// or `*/*`, allows images without a quality of zero.
+fn accepts_images(accept: &str) -> bool {
+    accept.split(',').any(|range| {
+        let mut params = range.split(';');
+        let media_type = params
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let excluded = params.any(|param| {
+            param
+                .split_once('=')
+                .filter(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, quality)| quality.trim().parse::<f32>().ok())
+                .map(|quality| quality <= 0.0)
+                .unwrap_or(false)
+        });
+        !excluded && (media_type.starts_with("image/") || media_type == "*/*")
+    })
+}
+
+impl fmt::Display for BotScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "score {}", self.total())?;
+        for (index, signal) in self.signals.iter().enumerate() {
+            f.write_str(if index == 0 { " (" } else { ", " })?;
+            write!(f, "{signal}")?;
+        }
+        if !self.signals.is_empty() {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+                          (KHTML, like Gecko) Chrome/118.0.0.0 Safari/537.36";
+
+    fn score(user_agent: &str, headers: &[(&'static str, &str)]) -> BotScore {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect();
+        BotScore::from_headers(&headers, user_agent)
+    }
+
+    #[test]
+    fn browser_image_request() {
+        let headers = [
+            (
+                "accept",
+                "image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8",
+            ),
+            ("accept-encoding", "gzip, deflate, br"),
+            ("accept-language", "en-US,en;q=0.9"),
+            (
+                "sec-ch-ua",
+                "\"Chromium\";v=\"118\", \"Google Chrome\";v=\"118\"",
+            ),
+            ("sec-fetch-dest", "image"),
+            ("sec-fetch-mode", "no-cors"),
+            ("sec-fetch-site", "cross-site"),
+        ];
+        assert_eq!(BotScore::default(), score(CHROME, &headers));
+        assert_eq!("score 0", score(CHROME, &headers).to_string());
+    }
+
+    #[test]
+    fn scraper_with_a_browser_user_agent() {
+        let score = score(CHROME, &[("accept", "text/html,application/xhtml+xml")]);
+        assert_eq!(
+            vec![
+                BotSignal::MissingAcceptLanguage,
+                BotSignal::MissingAcceptEncoding,
+                BotSignal::AcceptWithoutImages,
+                BotSignal::MissingFetchMetadata,
+            ],
+            score.signals()
+        );
+        assert_eq!(6, score.total());
+        assert_eq!(
+            "score 6 (no Accept-Language header, no Accept-Encoding header, \
+             Accept header doesn't allow images, browser user agent without Sec-Fetch headers)",
+            score.to_string()
+        );
+    }
+
+    #[test]
+    fn accept_header_allowing_images() {
+        let accept_signals = |accept: &str| {
+            score(CHROME, &[("accept", accept)])
+                .signals()
+                .iter()
+                .filter(|signal| **signal == BotSignal::AcceptWithoutImages)
+                .count()
+        };
+        assert_eq!(0, accept_signals("*/*"));
+        assert_eq!(0, accept_signals("image/*"));
+        assert_eq!(0, accept_signals("text/html, IMAGE/WEBP;q=0.9"));
+        assert_eq!(1, accept_signals("text/html"));
+        assert_eq!(1, accept_signals("text/html, image/*;q=0, */*; q=0.0"));
+        assert_eq!(
+            vec![
+                BotSignal::MissingAcceptLanguage,
+                BotSignal::MissingAcceptEncoding,
+                BotSignal::MissingAccept,
+                BotSignal::MissingFetchMetadata,
+            ],
+            score(CHROME, &[]).signals()
+        );
+    }
+
+    #[test]
+    fn client_hints_consistency() {
+        let firefox = "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/118.0";
+        let headers = [
+            ("accept", "image/avif,image/webp,*/*"),
+            ("accept-encoding", "gzip"),
+            ("accept-language", "en"),
+            ("sec-ch-ua", "\"HeadlessChrome\";v=\"118\""),
+            ("sec-fetch-dest", "empty"),
+        ];
+        assert_eq!(
+            vec![
+                BotSignal::UnexpectedFetchDest("empty".into()),
+                BotSignal::ClientHintsMismatch,
+                BotSignal::HeadlessClientHints,
+            ],
+            score(firefox, &headers).signals()
+        );
+        assert_eq!(
+            vec![
+                BotSignal::UnexpectedFetchDest("empty".into()),
+                BotSignal::HeadlessClientHints,
+            ],
+            score(CHROME, &headers).signals()
+        );
+    }
+
+    #[test]
+    fn browsers_without_fetch_metadata() {
+        // Older Safari versions don't send `Sec-Fetch-*` headers, so they aren't expected.
+        let safari = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 \
+                      (KHTML, like Gecko) Version/15.6 Safari/605.1.15";
+        let headers = [
+            ("accept", "image/webp,image/png,*/*;q=0.8"),
+            ("accept-encoding", "gzip"),
+            ("accept-language", "en"),
+        ];
+        assert_eq!(0, score(safari, &headers).total());
+        assert_eq!(2, score(CHROME, &headers).total());
+    }
+}
@@ -17,9 +17,32 @@
 
 //! Counter image rendering.
 
-use std::mem::size_of;
+pub mod animation;
+pub mod badge;
+pub mod canvas;
+pub mod font;
+pub mod metadata;
+mod palette;
+
+use font::BitmapFont;
+use metadata::PngMetadata;
+use palette::Palette;
+use std::{
+    error::Error as StdError,
+    fmt::{self, Write},
+    hash::{Hash, Hasher},
+    io, iter,
+    mem::size_of,
+    ops::Range,
+    str::FromStr,
+    sync::Arc,
+};
+
+/// The largest supported integer scale factor for renders.
+pub const MAX_SCALE: usize = 8;
 
 /// A rendered counter.
+#[derive(Clone, Default)]
 pub struct Render {
     /// Width in pixels.
     pub width: usize,
@@ -30,321 +53,1927 @@ pub struct Render {
 }
 
 impl Render {
+    /// Upscale this render by an integer factor using nearest-neighbour sampling,
+    /// which keeps the pixel font sharp instead of letting a browser smooth it.
+    ///
+    /// The factor is clamped between 1 and [`MAX_SCALE`].
+    pub fn scaled(self, factor: usize) -> Render {
+        let factor = factor.clamp(1, MAX_SCALE);
+        if factor == 1 {
+            return self;
+        }
+        let mut scaled = Render::default();
+        scale_into(&self.pixels, (self.width, self.height), factor, &mut scaled);
+        scaled
+    }
+
     /// Convert this render to an in-memory PNG image.
+    ///
+    /// Renders with up to 256 colors, which is nearly all of them, are written as palette-indexed
+    /// images at the smallest bit depth that fits the palette, which is usually a fraction of the
+    /// size of an RGBA image.
     pub fn to_png_bytes(&self) -> Result<Vec<u8>, png::EncodingError> {
+        self.to_png_bytes_with_metadata(&PngMetadata::default())
+    }
+
+    /// Like [`to_png_bytes`](Self::to_png_bytes), but with metadata chunks that describe the image.
+    pub fn to_png_bytes_with_metadata(
+        &self,
+        metadata: &PngMetadata,
+    ) -> Result<Vec<u8>, png::EncodingError> {
+        // Guestimate the size of the PNG and pre-allocate a buffer.
+        let mut png: Vec<u8> = Vec::with_capacity(self.width * self.height);
+        PngWriter::new().write_with_metadata(self, metadata, &mut png)?;
+        Ok(png)
+    }
+
+    /// Convert this render to an in-memory 32-bit RGBA PNG image, which is larger than
+    /// [`to_png_bytes`](Self::to_png_bytes) but faster to encode.
+    pub fn to_rgba_png_bytes(&self) -> Result<Vec<u8>, png::EncodingError> {
         // Guestimate the size of the PNG and pre-allocate a buffer.
         let mut png: Vec<u8> = Vec::with_capacity(self.pixels.len());
+        self.write_rgba_png(&PngMetadata::default(), &mut png)?;
+        Ok(png)
+    }
 
-        let mut encoder = png::Encoder::new(&mut png, self.width as u32, self.height as u32);
+    fn write_rgba_png(
+        &self,
+        metadata: &PngMetadata,
+        out: impl io::Write,
+    ) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        metadata.apply(&mut encoder)?;
         encoder.set_color(png::ColorType::Rgba);
         encoder.set_depth(png::BitDepth::Eight);
         let mut encoder = encoder.write_header()?;
         encoder.write_image_data(&self.pixels)?;
-        encoder.finish()?;
-        Ok(png)
+        encoder.finish()
+    }
+
+    /// Convert this render to an in-memory lossless WebP image.
+    pub fn to_webp_bytes(&self) -> Result<Vec<u8>, image_webp::EncodingError> {
+        let mut webp: Vec<u8> = Vec::new();
+        image_webp::WebPEncoder::new(&mut webp).encode(
+            &self.pixels,
+            self.width as u32,
+            self.height as u32,
+            image_webp::ColorType::Rgba8,
+        )?;
+        Ok(webp)
+    }
+
+    /// Convert this render to an in-memory GIF image.
+    ///
+    /// GIFs only support a single fully transparent color, so pixels that are less than half
+    /// opaque become transparent, and the rest become opaque.
+    pub fn to_gif_bytes(&self) -> Result<Vec<u8>, gif::EncodingError> {
+        let too_large = || {
+            gif::EncodingError::from(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "render is too large for a GIF",
+            ))
+        };
+        let width = u16::try_from(self.width).map_err(|_| too_large())?;
+        let height = u16::try_from(self.height).map_err(|_| too_large())?;
+
+        let flattened = Render {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .pixels
+                .chunks_exact(size_of::<u32>())
+                .flat_map(|pixel| match pixel[3] {
+                    0..=0x7F => [0, 0, 0, 0],
+                    _ => [pixel[0], pixel[1], pixel[2], 0xFF],
+                })
+                .collect(),
+        };
+        let palette = Palette::from_render(&flattened).ok_or(gif::EncodingError::from(
+            gif::EncodingFormatError::TooManyColors,
+        ))?;
+
+        let mut gif: Vec<u8> = Vec::new();
+        let mut encoder = gif::Encoder::new(&mut gif, width, height, &palette.rgb())?;
+        encoder.write_frame(&gif::Frame {
+            width,
+            height,
+            buffer: palette.index_bytes(&flattened).into(),
+            // The transparent color is sorted first.
+            transparent: palette.alpha().map(|_| 0),
+            ..Default::default()
+        })?;
+        encoder.into_inner()?;
+        Ok(gif)
+    }
+
+    /// Convert this render to an in-memory SVG image.
+    ///
+    /// Each horizontal run of same-colored pixels becomes a rectangle in a single
+    /// path per color, so the pixel font stays crisp at any size. The fills are
+    /// presentation attributes, which means CSS such as `fill: currentColor` on an
+    /// inlined SVG takes precedence over them.
+    pub fn to_svg_string(&self) -> String {
+        let paths = self.svg_paths();
+        let mut svg = String::with_capacity(paths.len() + 256);
+        self.write_svg_start(&mut svg);
+        svg.push_str(&paths);
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Convert this render to an SVG image that switches to a `dark` render of the same size,
+    /// such as the same number rendered with [dark colors](RenderStyle::dark_colors), when the
+    /// reader prefers a dark color scheme.
+    ///
+    /// Both renders are embedded in the image, and a `prefers-color-scheme` media query
+    /// shows one of them.
+    pub fn to_adaptive_svg_string(&self, dark: &Render) -> String {
+        debug_assert_eq!((self.width, self.height), (dark.width, dark.height));
+        let (light, dark) = (self.svg_paths(), dark.svg_paths());
+        let mut svg = String::with_capacity(light.len() + dark.len() + 512);
+        self.write_svg_start(&mut svg);
+        // The class names are prefixed since they can clash with a page's own when inlined.
+        svg.push_str(
+            "<style>.dgvc-dark{display:none}@media (prefers-color-scheme:dark){\
+             .dgvc-light{display:none}.dgvc-dark{display:inline}}</style>",
+        );
+        write!(svg, "<g class=\"dgvc-light\">{light}</g>").unwrap();
+        write!(svg, "<g class=\"dgvc-dark\">{dark}</g>").unwrap();
+        svg.push_str("</svg>");
+        svg
+    }
+
+    fn write_svg_start(&self, svg: &mut String) {
+        write!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\">",
+            w = self.width,
+            h = self.height
+        )
+        .unwrap();
+    }
+
+    /// Returns a path element for each distinct color of this render.
+    fn svg_paths(&self) -> String {
+        // Path data for each distinct color, in order of first appearance.
+        let mut paths: Vec<([u8; 4], String)> = Vec::new();
+        for y in 0..self.height {
+            let stride = self.width * size_of::<u32>();
+            let row = &self.pixels[y * stride..(y + 1) * stride];
+            let mut x = 0;
+            while x < self.width {
+                let color = pixel_at(row, x);
+                let run = (x..self.width)
+                    .take_while(|&end| pixel_at(row, end) == color)
+                    .count();
+                if color[3] != 0 {
+                    let path = match paths.iter_mut().find(|(c, _)| *c == color) {
+                        Some((_, path)) => path,
+                        None => {
+                            paths.push((color, String::new()));
+                            &mut paths.last_mut().unwrap().1
+                        }
+                    };
+                    write!(path, "M{x} {y}h{run}v1h-{run}z").unwrap();
+                }
+                x += run;
+            }
+        }
+
+        let mut svg = String::with_capacity(paths.iter().map(|(_, p)| p.len() + 64).sum());
+        for ([r, g, b, a], path) in paths {
+            write!(svg, "<path fill=\"#{r:02x}{g:02x}{b:02x}\"").unwrap();
+            if a != 0xFF {
+                write!(svg, " fill-opacity=\"{:.3}\"", a as f32 / 255.0).unwrap();
+            }
+            write!(svg, " d=\"{path}\"/>").unwrap();
+        }
+        svg
+    }
+}
+
+/// Return the RGBA value of the pixel at `x` in a row of 32-bit RGBA pixels.
+fn pixel_at(row: &[u8], x: usize) -> [u8; 4] {
+    let index = x * size_of::<u32>();
+    [row[index], row[index + 1], row[index + 2], row[index + 3]]
+}
+
+/// Upscale the pixels of a `(width, height)` image by an integer factor into `dest` using
+/// nearest-neighbour sampling, reusing the memory of its pixels.
+fn scale_into(pixels: &[u8], (width, height): (usize, usize), factor: usize, dest: &mut Render) {
+    dest.width = width * factor;
+    dest.height = height * factor;
+    let stride = dest.width * size_of::<u32>();
+    dest.pixels.clear();
+    dest.pixels.reserve(stride * dest.height);
+    for row in pixels.chunks_exact(width * size_of::<u32>()) {
+        let start = dest.pixels.len();
+        for pixel in row.chunks_exact(size_of::<u32>()) {
+            for _ in 0..factor {
+                dest.pixels.extend_from_slice(pixel);
+            }
+        }
+        // Repeat the scaled row for the remaining rows of this pixel.
+        for _ in 1..factor {
+            dest.pixels.extend_from_within(start..start + stride);
+        }
+    }
+}
+
+/// Writes renders as PNG images like [`Render::to_png_bytes`], while keeping the memory it needs
+/// for palette detection and indexing between images.
+///
+/// Reusing a writer for every image avoids most of the allocations of encoding, although the
+/// PNG encoder still allocates its own compression state for each image.
+#[derive(Default)]
+pub struct PngWriter {
+    palette: Palette,
+    rgb: Vec<u8>,
+    alpha: Vec<u8>,
+    indexed: Vec<u8>,
+}
+
+impl PngWriter {
+    /// Creates a writer with no memory reserved yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write a render as a PNG image to `out`.
+    pub fn write(
+        &mut self,
+        render: &Render,
+        out: impl io::Write,
+    ) -> Result<(), png::EncodingError> {
+        self.write_with_metadata(render, &PngMetadata::default(), out)
+    }
+
+    /// Write a render as a PNG image to `out`, with metadata chunks that describe the image.
+    pub fn write_with_metadata(
+        &mut self,
+        render: &Render,
+        metadata: &PngMetadata,
+        out: impl io::Write,
+    ) -> Result<(), png::EncodingError> {
+        if !self.palette.detect(render) {
+            return render.write_rgba_png(metadata, out);
+        }
+        self.palette.rgb_into(&mut self.rgb);
+        self.palette.alpha_into(&mut self.alpha);
+        self.palette.index_into(render, &mut self.indexed);
+
+        let mut encoder = png::Encoder::new(out, render.width as u32, render.height as u32);
+        metadata.apply(&mut encoder)?;
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(self.palette.bit_depth());
+        encoder.set_palette(&self.rgb[..]);
+        if !self.alpha.is_empty() {
+            encoder.set_trns(&self.alpha[..]);
+        }
+        // Filtering rarely helps palette images, and skipping it gave the smallest counters when
+        // every filter was compared. The best compression level costs tens of microseconds for
+        // typical counters, and up to about a millisecond at large scales.
+        encoder.set_filter(png::FilterType::NoFilter);
+        encoder.set_compression(png::Compression::Best);
+        let mut encoder = encoder.write_header()?;
+        encoder.write_image_data(&self.indexed)?;
+        encoder.finish()
+    }
+}
+
+/// A 32-bit RGBA color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    /// Opaque white.
+    pub const WHITE: Color = Color::rgba(0xFF, 0xFF, 0xFF, 0xFF);
+    /// Opaque black.
+    pub const BLACK: Color = Color::rgba(0x00, 0x00, 0x00, 0xFF);
+    /// Fully transparent black.
+    pub const TRANSPARENT: Color = Color::rgba(0x00, 0x00, 0x00, 0x00);
+
+    /// Creates a color from its red, green, blue, and alpha components.
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Returns the color in the byte order used by [`Render::pixels`].
+    pub const fn to_bytes(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// Returns the color halfway between this color and `other`, keeping this color's alpha.
+    fn mix(self, other: Color) -> Color {
+        let half = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+        Color::rgba(
+            half(self.r, other.r),
+            half(self.g, other.g),
+            half(self.b, other.b),
+            self.a,
+        )
+    }
+}
+
+/// An error parsing a [`Color`] from a string.
+#[derive(Debug)]
+pub struct ParseColorError(String);
+
+impl StdError for ParseColorError {}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid color `{}` (expected `rgb`, `rrggbb`, or `rrggbbaa` in hex)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses a hex color in `rgb`, `rrggbb`, or `rrggbbaa` format, with an optional leading `#`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let hex = value.strip_prefix('#').unwrap_or(value);
+        let err = || ParseColorError(value.into());
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(err());
+        }
+        let channel =
+            |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).map_err(|_| err());
+        match hex.len() {
+            3 => {
+                let expand = |index: usize| channel(index..index + 1).map(|c| c * 0x11);
+                Ok(Color::rgba(expand(0)?, expand(1)?, expand(2)?, 0xFF))
+            }
+            6 => Ok(Color::rgba(
+                channel(0..2)?,
+                channel(2..4)?,
+                channel(4..6)?,
+                0xFF,
+            )),
+            8 => Ok(Color::rgba(
+                channel(0..2)?,
+                channel(2..4)?,
+                channel(4..6)?,
+                channel(6..8)?,
+            )),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// How the digits of a number are split into groups.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Grouping {
+    /// Groups of 3 digits, such as `1 234 567`.
+    #[default]
+    Thousands,
+    /// Indian lakh and crore grouping, where the rightmost group has 3 digits and every
+    /// other group has 2 digits, such as `12,34,567`.
+    Indian,
+    /// No grouping.
+    None,
+}
+
+impl Grouping {
+    /// Split the digit positions of a number into groups, from left to right.
+    fn split_slots<'a, T>(&self, slots: &'a [T]) -> Vec<&'a [T]> {
+        let mut groups = Vec::new();
+        self.split_into(slots.len(), &mut groups);
+        groups.into_iter().map(|group| &slots[group]).collect()
+    }
+
+    /// Replace the contents of `groups` with the ranges of every group of `len` digit positions,
+    /// from left to right.
+    fn split_into(&self, len: usize, groups: &mut Vec<Range<usize>>) {
+        groups.clear();
+        let mut end = len;
+        let mut size = match self {
+            Grouping::Thousands | Grouping::Indian => 3,
+            Grouping::None => len,
+        };
+        while end > size {
+            groups.push(end - size..end);
+            end -= size;
+            if *self == Grouping::Indian {
+                size = 2;
+            }
+        }
+        groups.push(0..end);
+        groups.reverse();
+    }
+}
+
+/// What is placed between groups of digits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Separator {
+    /// A space that is about a third of the width of a digit.
+    #[default]
+    Space,
+    /// A space that is about a sixth of the width of a digit.
+    ThinSpace,
+    /// A comma glyph, such as `1,234`.
+    Comma,
+    /// A period glyph, such as `1.234`.
+    Period,
+    /// An apostrophe glyph, such as `1'234`.
+    Apostrophe,
+}
+
+impl Separator {
+    /// Returns the separator's glyph character, if it isn't a space.
+    fn glyph_char(&self) -> Option<char> {
+        match self {
+            Separator::Space | Separator::ThinSpace => None,
+            Separator::Comma => Some(','),
+            Separator::Period => Some('.'),
+            Separator::Apostrophe => Some('\''),
+        }
+    }
+
+    /// Returns the separator's character for text.
+    fn text_char(&self) -> char {
+        match self {
+            Separator::Space => ' ',
+            Separator::ThinSpace => '\u{2009}',
+            Separator::Comma => ',',
+            Separator::Period => '.',
+            Separator::Apostrophe => '\'',
+        }
+    }
+}
+
+/// Digit grouping conventions for a locale.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Locale {
+    /// How digits are grouped.
+    pub grouping: Grouping,
+    /// What is placed between each group.
+    pub separator: Separator,
+}
+
+impl Locale {
+    /// Format a number as text, with this locale's separator between each group of digits.
+    pub fn format(&self, number: usize) -> String {
+        let digits: Vec<char> = number.to_string().chars().collect();
+        let groups: Vec<String> = self
+            .grouping
+            .split_slots(&digits)
+            .into_iter()
+            .map(|group| group.iter().collect())
+            .collect();
+        groups.join(&self.separator.text_char().to_string())
+    }
+}
+
+/// An error parsing a [`Locale`] from a string.
+#[derive(Debug)]
+pub struct ParseLocaleError(String);
+
+impl StdError for ParseLocaleError {}
+
+impl fmt::Display for ParseLocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown locale `{}` (expected one of: space, thin-space, comma, period, apostrophe, indian, none)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Locale {
+    type Err = ParseLocaleError;
+
+    /// Parses a locale by the name of its separator, `indian` for Indian grouping with commas,
+    /// or `none` for no grouping.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let thousands = |separator| Locale {
+            grouping: Grouping::Thousands,
+            separator,
+        };
+        match value {
+            "space" => Ok(thousands(Separator::Space)),
+            "thin-space" => Ok(thousands(Separator::ThinSpace)),
+            "comma" => Ok(thousands(Separator::Comma)),
+            "period" => Ok(thousands(Separator::Period)),
+            "apostrophe" => Ok(thousands(Separator::Apostrophe)),
+            "indian" => Ok(Locale {
+                grouping: Grouping::Indian,
+                separator: Separator::Comma,
+            }),
+            "none" => Ok(Locale {
+                grouping: Grouping::None,
+                separator: Separator::default(),
+            }),
+            _ => Err(ParseLocaleError(value.into())),
+        }
+    }
+}
+
+/// What is drawn in the digit positions that are reserved but not used by the number.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Padding {
+    /// Reserved digits are left blank, so the number is right-aligned.
+    #[default]
+    Blank,
+    /// Reserved digits are filled with zeros, such as `000,123`.
+    Zeros,
+    /// Reserved digits are filled with the given character's glyph, such as `***,123`.
+    Fill(char),
+}
+
+/// An error parsing a [`Padding`] from a string.
+#[derive(Debug)]
+pub struct ParsePaddingError(String);
+
+impl StdError for ParsePaddingError {}
+
+impl fmt::Display for ParsePaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid padding `{}` (expected `blank`, `zeros`, or a single fill character)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Padding {
+    type Err = ParsePaddingError;
+
+    /// Parses `blank`, `zeros`, or a single character to fill with.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut chars = value.chars();
+        match (value, chars.next(), chars.next()) {
+            ("blank", _, _) => Ok(Padding::Blank),
+            ("zeros", _, _) => Ok(Padding::Zeros),
+            (_, Some(c), None) => Ok(Padding::Fill(c)),
+            _ => Err(ParsePaddingError(value.into())),
+        }
+    }
+}
+
+impl Padding {
+    /// Returns the character drawn in reserved digit positions, if any.
+    fn fill_char(&self) -> Option<char> {
+        match self {
+            Padding::Blank => None,
+            Padding::Zeros => Some('0'),
+            Padding::Fill(c) => Some(*c),
+        }
+    }
+}
+
+/// The most digits after the decimal point of a compact number.
+pub const MAX_PRECISION: usize = 3;
+
+/// How a number is written out.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Notation {
+    /// Every digit of the number, such as `12 345`.
+    #[default]
+    Full,
+    /// Thousands, millions, and billions are abbreviated with a unit, such as `12.3K`.
+    Compact,
+}
+
+/// An error parsing a [`Notation`] from a string.
+#[derive(Debug)]
+pub struct ParseNotationError(String);
+
+impl StdError for ParseNotationError {}
+
+impl fmt::Display for ParseNotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown notation `{}` (expected one of: full, compact)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Notation {
+    type Err = ParseNotationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "full" => Ok(Notation::Full),
+            "compact" => Ok(Notation::Compact),
+            _ => Err(ParseNotationError(value.into())),
+        }
+    }
+}
+
+impl Notation {
+    /// Units of compact numbers, starting from thousands.
+    const UNITS: [char; 3] = ['K', 'M', 'B'];
+    /// The most digits before the decimal point of an abbreviated number.
+    const COMPACT_INTEGER_DIGITS: usize = 3;
+
+    /// Returns the number of digits to reserve before the decimal point, given the minimum width
+    /// of the counter in digits. Compact numbers don't need more than three.
+    fn reserve_width(&self, reserve_width: usize) -> usize {
+        match self {
+            Notation::Full => reserve_width,
+            Notation::Compact => reserve_width.min(Self::COMPACT_INTEGER_DIGITS),
+        }
+    }
+}
+
+/// A number split into the parts that get laid out, after applying the style's [`Notation`].
+#[derive(Debug, Default, PartialEq, Eq)]
+struct FormattedNumber {
+    /// Digits before the decimal point, which are grouped.
+    integer: String,
+    /// Digits after the decimal point of an abbreviated number.
+    fraction: String,
+    /// Unit of an abbreviated number.
+    unit: Option<char>,
+}
+
+impl FormattedNumber {
+    fn new(number: usize, style: &RenderStyle) -> Self {
+        let mut formatted = Self::default();
+        formatted.format(number, style);
+        formatted
+    }
+
+    /// Replace this with another number, reusing the memory of its digits.
+    fn format(&mut self, number: usize, style: &RenderStyle) {
+        self.integer.clear();
+        self.fraction.clear();
+        let unit = match style.notation {
+            Notation::Full => None,
+            // The largest unit that the number reaches.
+            Notation::Compact => (1..=Notation::UNITS.len())
+                .rev()
+                .map(|power| (1000usize.pow(power as u32), Notation::UNITS[power - 1]))
+                .find(|&(divisor, _)| number >= divisor),
+        };
+        match unit {
+            Some((divisor, unit)) => {
+                // Digits past the precision are dropped so that the count is never overstated.
+                let precision = style.precision.min(MAX_PRECISION);
+                if precision > 0 {
+                    let fraction = number % divisor / (divisor / 10usize.pow(precision as u32));
+                    write!(self.fraction, "{fraction:0precision$}").unwrap();
+                }
+                write!(self.integer, "{}", number / divisor).unwrap();
+                self.unit = Some(unit);
+            }
+            None => {
+                write!(self.integer, "{number}").unwrap();
+                self.unit = None;
+            }
+        }
+    }
+}
+
+/// Boxes drawn around the digits, like an old hit counter.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Frame {
+    /// The digits are drawn directly on the background.
+    #[default]
+    None,
+    /// Every digit is drawn in its own sunken cell.
+    Cells,
+    /// Every digit is drawn in its own sunken cell, and everything is inside a raised frame.
+    Framed,
+}
+
+/// An error parsing a [`Frame`] from a string.
+#[derive(Debug)]
+pub struct ParseFrameError(String);
+
+impl StdError for ParseFrameError {}
+
+impl fmt::Display for ParseFrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown frame `{}` (expected one of: none, cells, framed)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Frame {
+    type Err = ParseFrameError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(Frame::None),
+            "cells" => Ok(Frame::Cells),
+            "framed" => Ok(Frame::Framed),
+            _ => Err(ParseFrameError(value.into())),
+        }
+    }
+}
+
+impl Frame {
+    /// Width of the bevelled border of each digit cell in pixels.
+    const CELL_BORDER: usize = 1;
+    /// Space between a digit cell's border and its digit in pixels.
+    const CELL_PADDING: usize = 1;
+    /// Width of the bevelled outer frame in pixels, which is a 1px bevel and a 1px face.
+    const FRAME_WIDTH: usize = 2;
+
+    /// Returns the space between the edge of a digit's cell and the digit in pixels.
+    fn cell_inset(&self) -> usize {
+        match self {
+            Frame::None => 0,
+            Frame::Cells | Frame::Framed => Self::CELL_BORDER + Self::CELL_PADDING,
+        }
+    }
+
+    /// Returns the width of the outer frame in pixels.
+    fn frame_width(&self) -> usize {
+        match self {
+            Frame::None | Frame::Cells => 0,
+            Frame::Framed => Self::FRAME_WIDTH,
+        }
+    }
+}
+
+/// Colors and font used when rendering a counter.
+#[derive(Clone, Debug)]
+pub struct RenderStyle {
+    /// Font to render the digits with.
+    pub font: Arc<dyn BitmapFont>,
+    /// Color of the digits.
+    pub foreground: Color,
+    /// Color of everything that isn't a digit.
+    pub background: Color,
+    /// Optional per-group digit colors that override the foreground color.
+    ///
+    /// The first color applies to the rightmost group of digits, the second color to the
+    /// group left of that, and so on. The colors repeat if there are more groups than colors.
+    pub group_colors: Vec<Color>,
+    /// Integer scale factor between 1 and [`MAX_SCALE`].
+    pub scale: usize,
+    /// How digits are grouped and separated.
+    pub locale: Locale,
+    /// What is drawn in the reserved digit positions to the left of the number.
+    pub padding: Padding,
+    /// Whether large numbers are abbreviated.
+    pub notation: Notation,
+    /// Number of digits after the decimal point of abbreviated numbers, up to [`MAX_PRECISION`].
+    pub precision: usize,
+    /// Label drawn to the left of the number, such as `Visitors: `.
+    pub prefix: String,
+    /// Label drawn to the right of the number.
+    pub suffix: String,
+    /// Boxes drawn around the digits.
+    pub frame: Frame,
+    /// Background color of the digit cells when they are enabled by the [`Frame`].
+    pub cell_color: Color,
+    /// Color of the outer frame when it is enabled by the [`Frame`], which is also the
+    /// background color inside of the frame.
+    pub frame_color: Color,
+    /// Colors to use instead when the reader prefers a dark color scheme, which only SVG
+    /// images can adapt to.
+    pub dark_colors: Option<DarkColors>,
+}
+
+/// Colors that replace a [`RenderStyle`]'s colors when the reader prefers a dark color scheme.
+///
+/// Colors that aren't set stay the same as the style's.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DarkColors {
+    /// Color of the digits.
+    pub foreground: Option<Color>,
+    /// Color of everything that isn't a digit.
+    pub background: Option<Color>,
+    /// Per-group digit colors.
+    pub group_colors: Option<Vec<Color>>,
+    /// Background color of the digit cells.
+    pub cell_color: Option<Color>,
+    /// Color of the outer frame.
+    pub frame_color: Option<Color>,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        Self {
+            font: font::builtin("classic").expect("classic is a built-in font"),
+            foreground: Color::WHITE,
+            background: Color::TRANSPARENT,
+            group_colors: Vec::new(),
+            scale: 1,
+            locale: Locale::default(),
+            padding: Padding::Blank,
+            notation: Notation::Full,
+            precision: 1,
+            prefix: String::new(),
+            suffix: String::new(),
+            frame: Frame::None,
+            cell_color: Color::rgba(0x20, 0x20, 0x20, 0xFF),
+            frame_color: Color::rgba(0xC0, 0xC0, 0xC0, 0xFF),
+            dark_colors: None,
+        }
+    }
+}
+
+// `PartialEq` can't be derived for structs with trait object fields (rust-lang/rust#31740),
+// and `Hash` needs to be implemented alongside it.
+impl PartialEq for RenderStyle {
+    fn eq(&self, other: &Self) -> bool {
+        *self.font == *other.font
+            && self.foreground == other.foreground
+            && self.background == other.background
+            && self.group_colors == other.group_colors
+            && self.scale == other.scale
+            && self.locale == other.locale
+            && self.padding == other.padding
+            && self.notation == other.notation
+            && self.precision == other.precision
+            && self.prefix == other.prefix
+            && self.suffix == other.suffix
+            && self.frame == other.frame
+            && self.cell_color == other.cell_color
+            && self.frame_color == other.frame_color
+            && self.dark_colors == other.dark_colors
+    }
+}
+
+impl Eq for RenderStyle {}
+
+impl Hash for RenderStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.font.hash(state);
+        self.foreground.hash(state);
+        self.background.hash(state);
+        self.group_colors.hash(state);
+        self.scale.hash(state);
+        self.locale.hash(state);
+        self.padding.hash(state);
+        self.notation.hash(state);
+        self.precision.hash(state);
+        self.prefix.hash(state);
+        self.suffix.hash(state);
+        self.frame.hash(state);
+        self.cell_color.hash(state);
+        self.frame_color.hash(state);
+        self.dark_colors.hash(state);
+    }
+}
+
+impl RenderStyle {
+    /// Returns this style with its [dark colors](Self::dark_colors) in place of its colors,
+    /// or `None` if it doesn't have any.
+    pub fn dark_style(&self) -> Option<RenderStyle> {
+        let dark = self.dark_colors.as_ref()?;
+        Some(RenderStyle {
+            foreground: dark.foreground.unwrap_or(self.foreground),
+            background: dark.background.unwrap_or(self.background),
+            group_colors: dark
+                .group_colors
+                .clone()
+                .unwrap_or_else(|| self.group_colors.clone()),
+            cell_color: dark.cell_color.unwrap_or(self.cell_color),
+            frame_color: dark.frame_color.unwrap_or(self.frame_color),
+            dark_colors: None,
+            ..self.clone()
+        })
+    }
+
+    /// Returns the digit color for the group at `index`, counting from the rightmost group.
+    fn group_color(&self, index: usize) -> Color {
+        if self.group_colors.is_empty() {
+            self.foreground
+        } else {
+            self.group_colors[index % self.group_colors.len()]
+        }
     }
 }
 
-/// Render a number with a space between every 3 digits.
+/// Render a number with its digits grouped according to the style's [`Locale`], and with
+/// the style's prefix and suffix labels on either side.
 ///
 /// The `reserve_width` is a minimum width of the image in number of digits.
 /// This is useful if you want the image to always be the same width.
-pub fn render_separated_number(number: usize, reserve_width: usize) -> Render {
-    let number = number.to_string();
-
-    // Spacing between groups of digits in pixels.
-    let group_spacing = 3;
-
-    // Split the number into groups of 3 digits, starting from the right.
-    let groups: Vec<_> = number
-        .as_bytes()
-        .rchunks(3)
-        .rev()
-        .map(|b| std::str::from_utf8(b).unwrap())
+/// The reserved digits are filled according to the style's [`Padding`]. In compact [`Notation`],
+/// at most three digits are reserved before the decimal point.
+pub fn render_separated_number(number: usize, reserve_width: usize, style: &RenderStyle) -> Render {
+    let mut buffer = RenderBuffer::new();
+    render_separated_number_into(number, reserve_width, style, &mut buffer);
+    buffer.into_render()
+}
+
+/// Memory for rendering counters with [`render_separated_number_into`], which is reused by
+/// every render so that rendering doesn't allocate once the buffer is large enough.
+#[derive(Default)]
+pub struct RenderBuffer {
+    layout: NumberLayout,
+    /// Pixels before scaling, if the style is scaled.
+    unscaled: Vec<u8>,
+    render: Render,
+}
+
+impl RenderBuffer {
+    /// Creates a buffer with no memory reserved yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last render.
+    pub fn render(&self) -> &Render {
+        &self.render
+    }
+
+    /// Returns the last render, giving up the buffer.
+    pub fn into_render(self) -> Render {
+        self.render
+    }
+}
+
+/// Like [`render_separated_number`], but renders into a reusable buffer instead of allocating.
+///
+/// Returns the render, which stays in the buffer until the next render.
+pub fn render_separated_number_into<'a>(
+    number: usize,
+    reserve_width: usize,
+    style: &RenderStyle,
+    buffer: &'a mut RenderBuffer,
+) -> &'a Render {
+    let reserve_width = style.notation.reserve_width(reserve_width);
+    let RenderBuffer {
+        layout,
+        unscaled,
+        render,
+    } = buffer;
+    layout.lay_out(number, reserve_width, style);
+    let factor = style.scale.clamp(1, MAX_SCALE);
+    if factor == 1 {
+        layout.render_into(style, &mut render.pixels);
+        render.width = layout.width;
+        render.height = layout.height;
+    } else {
+        layout.render_into(style, unscaled);
+        scale_into(unscaled, (layout.width, layout.height), factor, render);
+    }
+    render
+}
+
+/// Render several labeled numbers into one image, such as `Today 12`, `Week 80`, and `Total 12 345`,
+/// placed one after the other in the given direction.
+///
+/// Each number is rendered like [`render_separated_number`] with its label in place of the
+/// style's prefix. In a column, the labels are padded with spaces to the same length so that the
+/// numbers line up when the font's label glyphs are as wide as its digits, like the built-in fonts.
+pub fn render_composite(
+    numbers: &[(&str, usize)],
+    reserve_width: usize,
+    style: &RenderStyle,
+    direction: canvas::Direction,
+) -> Render {
+    let label_len = numbers
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let renders: Vec<Render> = numbers
+        .iter()
+        .map(|&(label, number)| {
+            let prefix = match direction {
+                canvas::Direction::Row => format!("{label} "),
+                canvas::Direction::Column => format!("{label:<label_len$} "),
+            };
+            let style = RenderStyle {
+                prefix,
+                ..style.clone()
+            };
+            render_separated_number(number, reserve_width, &style)
+        })
         .collect();
+    // Rows are separated by about a digit, while each number's margin is enough in a column.
+    let gap = match direction {
+        canvas::Direction::Row => style.font.glyph_size().0 * style.scale.clamp(1, MAX_SCALE),
+        canvas::Direction::Column => 0,
+    };
+    canvas::stack(&renders, direction, gap, style.background)
+}
 
-    // Calculate the image size and allocate memory.
-    let (width, height) = font::text_size(number.len().max(reserve_width));
-    let width = 2 + width + group_spacing * (reserve_width / 3).max(number.len() / 3);
-    let height = 2 + height; // 2px padding total
-    let mut pixels = vec![0; width * height * size_of::<u32>()];
-
-    // Calculate the very first X offset such that the number ends up right-aligned.
-    let mut x = 1 // 1px padding on the left
-        // First, calculate offset in character widths
-        + Some(reserve_width as isize - number.len() as isize)
-            .filter(|&n| n > 0)
-            .map(|n| font::text_size(n as usize).0)
-            .unwrap_or(0)
-        // Then refine the offset in number of group spaces skipped
-        + Some((reserve_width / 3) as isize - (number.len() / 3) as isize)
-            .filter(|&n| n > 0)
-            .map(|n| n as usize * group_spacing)
-            .unwrap_or(0);
-    for group in groups {
-        font::blit_into(&mut pixels, width, group, x, 1);
-        x += font::text_size(group.len()).0 + group_spacing;
-    }
-
-    Render {
-        width,
-        height,
-        pixels,
-    }
-}
-
-mod font {
-    use std::mem::size_of;
-
-    pub const GLYPH_WIDTH: usize = 8;
-    pub const GLYPH_HEIGHT: usize = 16;
-    pub const GLYPH_SIZE: usize = GLYPH_WIDTH * GLYPH_HEIGHT;
-    pub const GLYPH_KERN: usize = 1;
-    pub const GLYPH_COUNT: usize = 10;
-
-    /// Blit a number into the given buffer.
-    pub fn blit_into(
-        buffer: &mut [u8],
-        buffer_width: usize,
-        text: &str,
-        offset_x: usize,
-        offset_y: usize,
-    ) {
-        let mut x = offset_x;
-        for c in text.chars() {
-            if let Some(glyph) = glyph_for_char(c) {
-                blit_glyph_into(buffer, buffer_width, glyph, x, offset_y);
-                x += GLYPH_WIDTH + GLYPH_KERN;
-            }
-        }
+/// Where each digit of a number is placed in an unscaled render.
+///
+/// Numbers laid out with the same reserved width place the digits in the same
+/// position (counting from the right) at the same X offset.
+#[derive(Default)]
+struct NumberLayout {
+    /// Width in pixels.
+    width: usize,
+    /// Height in pixels.
+    height: usize,
+    /// Y offset of the top of every digit.
+    y: usize,
+    /// Digits from left to right.
+    digits: Vec<PlacedDigit>,
+    /// Label and separator characters from left to right.
+    text: Vec<PlacedChar>,
+    /// X offsets of the left of every digit cell, including cells for reserved digits.
+    /// This is empty if the frame doesn't have cells.
+    cells: Vec<usize>,
+    /// Size of every digit cell in pixels as `(width, height)`.
+    cell_size: (usize, usize),
+    /// Scratch space for the number being laid out, kept so that layouts can be reused.
+    formatted: FormattedNumber,
+    /// Scratch space for the characters of every digit position.
+    slots: Vec<Option<char>>,
+    /// Scratch space for the ranges of every group of digit positions.
+    groups: Vec<Range<usize>>,
+}
+
+/// A single digit in a [`NumberLayout`].
+struct PlacedDigit {
+    /// The digit character.
+    digit: char,
+    /// X offset of the left of the digit.
+    x: usize,
+    /// Index of the digit's group, counting from the rightmost group.
+    group: usize,
+}
+
+/// A label or separator character in a [`NumberLayout`].
+struct PlacedChar {
+    /// The character.
+    c: char,
+    /// X offset of the left of the character.
+    x: usize,
+}
+
+impl NumberLayout {
+    fn new(number: usize, reserve_width: usize, style: &RenderStyle) -> Self {
+        let mut layout = Self::default();
+        layout.lay_out(number, reserve_width, style);
+        layout
     }
 
-    fn blit_glyph_into(
-        buffer: &mut [u8],
-        buffer_width: usize,
-        glyph: &[u8; GLYPH_SIZE],
-        x: usize,
-        y: usize,
-    ) {
-        for row in 0..GLYPH_HEIGHT {
-            for col in 0..GLYPH_WIDTH {
-                let dest_index = ((y + row) * buffer_width + (x + col)) * size_of::<u32>();
-                match glyph[row * GLYPH_WIDTH + col] {
-                    0 => {
-                        buffer[dest_index] = 0x00;
-                        buffer[dest_index + 1] = 0x00;
-                        buffer[dest_index + 2] = 0x00;
-                        buffer[dest_index + 3] = 0x00;
-                    }
-                    _ => {
-                        buffer[dest_index] = 0xFF;
-                        buffer[dest_index + 1] = 0xFF;
-                        buffer[dest_index + 2] = 0xFF;
-                        buffer[dest_index + 3] = 0xFF;
+    /// Replace this layout with the layout of another number, reusing its memory.
+    fn lay_out(&mut self, number: usize, reserve_width: usize, style: &RenderStyle) {
+        let font = &*style.font;
+        self.formatted.format(number, style);
+        let formatted = &self.formatted;
+        let number = &formatted.integer;
+        let Locale {
+            grouping,
+            separator,
+        } = style.locale;
+
+        // The separator glyph, if the font has one. Otherwise, fall back to a space.
+        let separator_glyph = separator
+            .glyph_char()
+            .and_then(|c| font.glyph(c).map(|glyph| (c, glyph.width)));
+        // Spacing between groups of digits in pixels, which is 3px for the 8px wide classic font.
+        let group_spacing = match (separator, separator_glyph) {
+            (_, Some((_, width))) => width + font.kerning(),
+            (Separator::ThinSpace, None) => font.glyph_size().0 / 6 + 1,
+            (_, None) => font.glyph_size().0 / 3 + 1,
+        };
+
+        // Labels are laid out glyph by glyph, since their glyphs may be narrower than the digits.
+        // Characters that the font doesn't have are left blank.
+        let advance = |c: char| canvas::char_advance(font, c);
+
+        // Digits are drawn inside of their cells, if there are any.
+        let inset = style.frame.cell_inset();
+        let (glyph_width, glyph_height) = font.glyph_size();
+        let cell_size = (glyph_width + 2 * inset, glyph_height + 2 * inset);
+        let digit_advance = cell_size.0 + font.kerning();
+        // 1px padding on every side, plus the outer frame
+        let margin = 1 + style.frame.frame_width();
+
+        // Lay out every digit position, including the reserved ones to the left of the number
+        // so that it ends up right-aligned. Padding is grouped and separated like any other digit.
+        let reserved = number.len().max(reserve_width) - number.len();
+        let slots = &mut self.slots;
+        slots.clear();
+        slots.resize(reserved, style.padding.fill_char());
+        slots.extend(number.chars().map(Some));
+        grouping.split_into(slots.len(), &mut self.groups);
+        let groups = &self.groups;
+
+        let mut x = margin;
+        let text = &mut self.text;
+        text.clear();
+        for c in style.prefix.chars() {
+            text.push(PlacedChar { c, x });
+            x += advance(c);
+        }
+
+        let digits = &mut self.digits;
+        let cells = &mut self.cells;
+        digits.clear();
+        cells.clear();
+        for (index, group) in groups.iter().enumerate() {
+            if index > 0 {
+                // Only separate digits and padding, and not blank reserved space.
+                if let Some((c, _)) = separator_glyph {
+                    if matches!(slots[..group.start].last(), Some(Some(_))) {
+                        text.push(PlacedChar { c, x });
                     }
                 }
+                x += group_spacing;
+            }
+            for slot in &slots[group.clone()] {
+                if inset > 0 {
+                    cells.push(x);
+                }
+                if let Some(digit) = *slot {
+                    digits.push(PlacedDigit {
+                        digit,
+                        x: x + inset,
+                        group: groups.len() - 1 - index,
+                    });
+                }
+                x += digit_advance;
             }
         }
+
+        // Compact notation always reserves the decimal point, fraction, and unit, so that the
+        // digits before the decimal point stay in place whether or not the number is abbreviated.
+        if style.notation == Notation::Compact {
+            let precision = style.precision.min(MAX_PRECISION);
+            // Numbers that aren't abbreviated have no fraction or unit, so their slots are blank.
+            let tail = formatted
+                .fraction
+                .chars()
+                .chain(formatted.unit)
+                .map(Some)
+                .chain(iter::repeat(None))
+                .take(precision + 1);
+            if precision > 0 {
+                if formatted.unit.is_some() {
+                    text.push(PlacedChar { c: '.', x });
+                }
+                x += advance('.');
+            }
+            for slot in tail {
+                if inset > 0 {
+                    cells.push(x);
+                }
+                if let Some(digit) = slot {
+                    digits.push(PlacedDigit {
+                        digit,
+                        x: x + inset,
+                        group: 0,
+                    });
+                }
+                x += digit_advance;
+            }
+        }
+
+        for c in style.suffix.chars() {
+            text.push(PlacedChar { c, x });
+            x += advance(c);
+        }
+
+        self.width = x + margin;
+        self.height = cell_size.1 + 2 * margin;
+        self.y = margin + inset;
+        self.cell_size = cell_size;
     }
 
-    pub fn glyph_for_char(c: char) -> Option<&'static [u8; GLYPH_SIZE]> {
-        if c.is_ascii_digit() {
-            let index = c as usize - '0' as usize;
-            Some(&GLYPH_BITMAPS[index])
-        } else {
-            None
-        }
-    }
-
-    /// Return the bitmap size for the given string length
-    pub fn text_size(char_count: usize) -> (usize, usize) {
-        (char_count * (GLYPH_WIDTH + GLYPH_KERN), GLYPH_HEIGHT)
-    }
-
-    /// Foreground
-    const F: u8 = 1;
-
-    #[rustfmt::skip]
-    const GLYPH_BITMAPS: [[u8; GLYPH_SIZE]; GLYPH_COUNT] = [
-        [ // 0
-            0,0,0,F,F,0,0,0,
-            0,0,F,F,F,F,0,0,
-            0,F,F,0,0,F,F,0,
-            0,F,0,0,0,0,F,0,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,F,0,F,F,
-            F,F,0,F,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            0,F,0,0,0,0,F,0,
-            0,F,F,0,0,F,F,0,
-            0,0,F,F,F,F,0,0,
-            0,0,0,F,F,0,0,0,
-        ],
-        [ // 1
-            0,0,0,F,F,0,0,0,
-            0,0,F,F,F,0,0,0,
-            0,F,F,F,F,0,0,0,
-            F,F,0,F,F,0,0,0,
-            F,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            F,F,F,F,F,F,F,F,
-            F,F,F,F,F,F,F,F,
-        ],
-        [ // 2
-            0,0,0,F,F,0,0,0,
-            0,F,F,F,F,F,0,0,
-            0,F,F,0,0,F,F,0,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,F,F,0,0,
-            0,0,0,0,F,F,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,F,F,0,0,0,0,
-            0,F,F,F,0,0,0,0,
-            F,F,F,F,F,F,F,F,
-            F,F,F,F,F,F,F,F,
-        ],
-        [ // 3
-            0,0,0,F,F,0,0,0,
-            0,F,F,F,F,F,F,0,
-            F,F,F,0,0,F,F,0,
-            F,F,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,F,F,0,
-            0,0,0,F,F,F,0,0,
-            0,0,0,F,F,F,0,0,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,F,0,0,F,F,0,
-            0,F,F,F,F,F,F,0,
-            0,0,0,F,F,0,0,0,
-        ],
-        [ // 4
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,F,F,F,0,
-            0,0,0,F,F,F,F,0,
-            0,0,0,F,0,F,F,0,
-            0,0,F,F,0,F,F,0,
-            0,F,F,0,0,F,F,0,
-            0,F,F,0,0,F,F,0,
-            F,F,0,0,0,F,F,0,
-            F,F,F,F,F,F,F,F,
-            F,F,F,F,F,F,F,F,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,F,F,F,F,
-            0,0,0,0,F,F,F,F,
-        ],
-        [ // 5
-            F,F,F,F,F,F,F,0,
-            F,F,F,F,F,F,F,0,
-            F,F,0,0,0,0,0,0,
-            F,F,0,0,0,0,0,0,
-            F,F,0,0,0,0,0,0,
-            F,F,0,0,0,0,0,0,
-            F,F,0,F,F,F,0,0,
-            F,F,F,F,F,F,F,0,
-            0,F,0,0,0,F,F,0,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,F,0,0,F,F,0,
-            0,F,F,F,F,F,0,0,
-            0,0,0,F,F,0,0,0,
-        ],
-        [ // 6
-            0,0,0,F,F,0,0,0,
-            0,F,F,F,F,F,F,0,
-            0,F,F,0,0,F,F,0,
-            F,F,0,0,0,0,0,0,
-            F,F,0,0,0,0,0,0,
-            F,F,0,0,0,0,0,0,
-            F,F,0,F,F,0,0,0,
-            F,F,F,F,F,F,F,0,
-            F,F,F,0,0,F,F,0,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            0,F,0,0,0,0,F,0,
-            0,F,F,0,0,F,F,0,
-            0,F,F,F,F,F,F,0,
-            0,0,0,F,F,0,0,0,
-        ],
-        [ // 7
-            F,F,F,F,F,F,F,F,
-            F,F,F,F,F,F,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,0,F,F,0,
-            0,0,0,0,F,F,0,0,
-            0,0,0,0,F,F,0,0,
-            0,0,F,F,F,F,F,0,
-            0,0,F,F,F,F,F,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-            0,0,0,F,F,0,0,0,
-        ],
-        [ // 8
-            0,0,0,F,F,0,0,0,
-            0,F,F,F,F,F,F,0,
-            0,F,F,0,0,F,F,0,
-            0,F,0,0,0,0,F,0,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            0,F,F,0,0,F,F,0,
-            0,F,F,F,F,F,F,0,
-            0,0,F,F,F,F,0,0,
-            0,F,F,0,0,F,F,0,
-            F,F,F,0,0,F,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            0,F,F,0,0,F,F,0,
-            0,F,F,F,F,F,F,0,
-            0,0,0,F,F,0,0,0,
-        ],
-        [ // 9
-            0,0,0,F,F,0,0,0,
-            0,F,F,F,F,F,F,0,
-            0,F,F,0,0,F,F,0,
-            0,F,0,0,0,0,F,0,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            F,F,0,0,0,0,F,F,
-            0,F,F,0,0,F,F,F,
-            0,F,F,F,F,F,F,F,
-            0,0,0,F,F,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,0,0,0,0,0,F,F,
-            0,F,F,0,0,F,F,0,
-            0,F,F,F,F,F,F,0,
-            0,0,0,F,F,0,0,0,
-        ],
-    ];
+    /// Render the digits without scaling.
+    fn render(&self, style: &RenderStyle) -> Render {
+        let mut pixels = Vec::new();
+        self.render_into(style, &mut pixels);
+        Render {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+
+    /// Render the digits without scaling, replacing the contents of `pixels`.
+    fn render_into(&self, style: &RenderStyle, pixels: &mut Vec<u8>) {
+        pixels.clear();
+        pixels.resize(self.width * self.height * size_of::<u32>(), 0);
+        fill_rect(
+            pixels,
+            self.width,
+            (0, 0),
+            (self.width, self.height),
+            style.background,
+        );
+        if style.frame == Frame::Framed {
+            let face = style.frame_color;
+            fill_rect(pixels, self.width, (0, 0), (self.width, self.height), face);
+            draw_bevel(
+                pixels,
+                self.width,
+                (0, 0),
+                (self.width, self.height),
+                face.mix(Color::WHITE),
+                face.mix(Color::BLACK),
+            );
+        }
+        for &x in &self.cells {
+            let position = (x, self.y - style.frame.cell_inset());
+            let cell = style.cell_color;
+            fill_rect(pixels, self.width, position, self.cell_size, cell);
+            // Cells are sunken, so they are lit from the bottom right.
+            draw_bevel(
+                pixels,
+                self.width,
+                position,
+                self.cell_size,
+                cell.mix(Color::BLACK),
+                cell.mix(Color::WHITE),
+            );
+        }
+        for digit in &self.digits {
+            let color = style.group_color(digit.group);
+            font::blit_char_into(
+                &*style.font,
+                pixels,
+                self.width,
+                digit.digit,
+                color,
+                digit.x,
+                self.y,
+            );
+        }
+        for placed in &self.text {
+            font::blit_char_into(
+                &*style.font,
+                pixels,
+                self.width,
+                placed.c,
+                style.foreground,
+                placed.x,
+                self.y,
+            );
+        }
+    }
+}
+
+/// Fill a rectangle of a pixel buffer that is `buffer_width` pixels wide with a color.
+fn fill_rect(
+    buffer: &mut [u8],
+    buffer_width: usize,
+    (x, y): (usize, usize),
+    (width, height): (usize, usize),
+    color: Color,
+) {
+    let stride = buffer_width * size_of::<u32>();
+    for row in y..y + height {
+        let start = row * stride + x * size_of::<u32>();
+        for pixel in buffer[start..start + width * size_of::<u32>()].chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color.to_bytes());
+        }
+    }
+}
+
+/// Draw a 1px bevel just inside the edge of a rectangle, with the `light` color on the top
+/// and left edges, and the `dark` color on the bottom and right edges.
+fn draw_bevel(
+    buffer: &mut [u8],
+    buffer_width: usize,
+    (x, y): (usize, usize),
+    (width, height): (usize, usize),
+    light: Color,
+    dark: Color,
+) {
+    fill_rect(buffer, buffer_width, (x, y), (width, 1), light);
+    fill_rect(buffer, buffer_width, (x, y), (1, height), light);
+    fill_rect(buffer, buffer_width, (x, y + height - 1), (width, 1), dark);
+    fill_rect(buffer, buffer_width, (x + width - 1, y), (1, height), dark);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_merges_runs_per_color() {
+        #[rustfmt::skip]
+        let render = Render {
+            width: 3,
+            height: 2,
+            pixels: vec![
+                0xFF, 0xFF, 0xFF, 0xFF,  0xFF, 0xFF, 0xFF, 0xFF,  0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00,  0x10, 0x20, 0x30, 0x80,  0xFF, 0xFF, 0xFF, 0xFF,
+            ],
+        };
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"3\" height=\"2\" \
+             viewBox=\"0 0 3 2\" shape-rendering=\"crispEdges\">\
+             <path fill=\"#ffffff\" d=\"M0 0h2v1h-2zM2 1h1v1h-1z\"/>\
+             <path fill=\"#102030\" fill-opacity=\"0.502\" d=\"M1 1h1v1h-1z\"/>\
+             </svg>",
+            render.to_svg_string()
+        );
+    }
+
+    #[test]
+    fn svg_matches_render_size() {
+        let render = render_separated_number(1234, 5, &RenderStyle::default());
+        let svg = render.to_svg_string();
+        assert!(svg.starts_with(&format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\"",
+            render.width, render.height
+        )));
+    }
+
+    #[test]
+    fn adaptive_svg_switches_renders() {
+        let light = Render {
+            width: 1,
+            height: 1,
+            pixels: vec![0, 0, 0, 0xFF],
+        };
+        let dark = Render {
+            width: 1,
+            height: 1,
+            pixels: vec![0xFF, 0xFF, 0xFF, 0xFF],
+        };
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1\" height=\"1\" \
+             viewBox=\"0 0 1 1\" shape-rendering=\"crispEdges\">\
+             <style>.dgvc-dark{display:none}@media (prefers-color-scheme:dark){\
+             .dgvc-light{display:none}.dgvc-dark{display:inline}}</style>\
+             <g class=\"dgvc-light\"><path fill=\"#000000\" d=\"M0 0h1v1h-1z\"/></g>\
+             <g class=\"dgvc-dark\"><path fill=\"#ffffff\" d=\"M0 0h1v1h-1z\"/></g>\
+             </svg>",
+            light.to_adaptive_svg_string(&dark)
+        );
+    }
+
+    #[test]
+    fn dark_style_keeps_unset_colors() {
+        assert!(RenderStyle::default().dark_style().is_none());
+
+        let style = RenderStyle {
+            foreground: Color::BLACK,
+            group_colors: vec![Color::BLACK, Color::WHITE],
+            frame: Frame::Framed,
+            dark_colors: Some(DarkColors {
+                foreground: Some(Color::WHITE),
+                background: Some(Color::BLACK),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let dark = style.dark_style().unwrap();
+        assert_eq!(Color::WHITE, dark.foreground);
+        assert_eq!(Color::BLACK, dark.background);
+        assert_eq!(style.group_colors, dark.group_colors);
+        assert_eq!(style.cell_color, dark.cell_color);
+        assert_eq!(Frame::Framed, dark.frame);
+        assert_eq!(None, dark.dark_colors);
+    }
+
+    #[test]
+    fn parse_color() {
+        assert_eq!(Color::rgba(0x11, 0x22, 0x33, 0xFF), "123".parse().unwrap());
+        assert_eq!(
+            Color::rgba(0x12, 0x34, 0x56, 0xFF),
+            "#123456".parse().unwrap()
+        );
+        assert_eq!(
+            Color::rgba(0x12, 0x34, 0x56, 0x78),
+            "12345678".parse().unwrap()
+        );
+        assert!("".parse::<Color>().is_err());
+        assert!("#12345".parse::<Color>().is_err());
+        assert!("ggg".parse::<Color>().is_err());
+        assert!("+12345".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn style_colors() {
+        let style = RenderStyle {
+            foreground: Color::rgba(1, 2, 3, 4),
+            background: Color::rgba(5, 6, 7, 8),
+            ..Default::default()
+        };
+        let render = render_separated_number(7, 1, &style);
+        assert_eq!(&[5, 6, 7, 8], &render.pixels[0..4], "corner is background");
+        assert!(render.pixels.chunks(4).any(|pixel| pixel == [1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn group_colors_start_from_the_right() {
+        let red = Color::rgba(0xFF, 0, 0, 0xFF);
+        let blue = Color::rgba(0, 0, 0xFF, 0xFF);
+        let style = RenderStyle {
+            group_colors: vec![red, blue],
+            ..Default::default()
+        };
+        let render = render_separated_number(1_000, 4, &style);
+        let color_at_column = |x: usize| {
+            (0..render.height)
+                .map(|y| pixel_at(&render.pixels[y * render.width * 4..], x))
+                .find(|pixel| pixel[3] != 0)
+        };
+        // The leading "1" is in the second group from the right.
+        assert_eq!(Some(blue.to_bytes()), color_at_column(5));
+        // The trailing "0" is in the rightmost group.
+        assert_eq!(Some(red.to_bytes()), color_at_column(render.width - 5));
+    }
+
+    #[test]
+    fn grouping_splits_from_the_right() {
+        let split = |grouping: Grouping, number: &str| -> Vec<String> {
+            let digits: Vec<char> = number.chars().collect();
+            let groups = grouping.split_slots(&digits);
+            groups.iter().map(|group| group.iter().collect()).collect()
+        };
+        assert_eq!(
+            vec!["1", "234", "567"],
+            split(Grouping::Thousands, "1234567")
+        );
+        assert_eq!(vec!["123"], split(Grouping::Thousands, "123"));
+        assert_eq!(
+            vec!["1", "23", "45", "678"],
+            split(Grouping::Indian, "12345678")
+        );
+        assert_eq!(vec!["1", "234"], split(Grouping::Indian, "1234"));
+        assert_eq!(vec!["1234567"], split(Grouping::None, "1234567"));
+    }
+
+    #[test]
+    fn parse_locale() {
+        assert_eq!(Locale::default(), "space".parse().unwrap());
+        assert_eq!(
+            Locale {
+                grouping: Grouping::Indian,
+                separator: Separator::Comma
+            },
+            "indian".parse().unwrap()
+        );
+        assert_eq!(
+            Separator::Apostrophe,
+            "apostrophe".parse::<Locale>().unwrap().separator
+        );
+        assert!("en-US".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn format_with_locale() {
+        let format = |locale: &str, number| locale.parse::<Locale>().unwrap().format(number);
+        assert_eq!("1,234,567", format("comma", 1_234_567));
+        assert_eq!("12,34,567", format("indian", 1_234_567));
+        assert_eq!("1\u{2009}234", format("thin-space", 1234));
+        assert_eq!("1234567", format("none", 1_234_567));
+        assert_eq!("123", format("period", 123));
+        assert_eq!("0", format("space", 0));
+    }
+
+    #[test]
+    fn parse_frame() {
+        assert_eq!(Frame::None, "none".parse().unwrap());
+        assert_eq!(Frame::Cells, "cells".parse().unwrap());
+        assert_eq!(Frame::Framed, "framed".parse().unwrap());
+        assert!("fancy".parse::<Frame>().is_err());
+    }
+
+    #[test]
+    fn separators_take_the_place_of_group_spaces() {
+        let render = |locale: &str, number| {
+            let style = RenderStyle {
+                locale: locale.parse().unwrap(),
+                ..Default::default()
+            };
+            render_separated_number(number, 7, &style)
+        };
+        // The classic font's narrow separators are as wide as its group space.
+        assert_eq!(
+            render("space", 1_234_567).width,
+            render("comma", 1_234_567).width
+        );
+        assert!(render("thin-space", 1_234_567).width < render("space", 1_234_567).width);
+        assert!(render("none", 1_234_567).width < render("thin-space", 1_234_567).width);
+
+        // Only the comma between the digits is drawn, and not the one in the reserved space.
+        let comma = render("comma", 1_234);
+        let space = render("space", 1_234);
+        let differences = comma
+            .pixels
+            .chunks_exact(4)
+            .zip(space.pixels.chunks_exact(4))
+            .filter(|(a, b)| a != b)
+            .count();
+        assert_eq!(6, differences);
+    }
+
+    #[test]
+    fn labels_are_drawn_around_the_number() {
+        let plain = render_separated_number(42, 5, &RenderStyle::default());
+        let labelled = render_separated_number(
+            42,
+            5,
+            &RenderStyle {
+                prefix: "#".into(),
+                suffix: "!".into(),
+                ..Default::default()
+            },
+        );
+        // Each label character is as wide as a digit in the classic font.
+        let char_width = font::CLASSIC.text_size(1).0;
+        assert_eq!(plain.width + 2 * char_width, labelled.width);
+
+        // The number is the same, but shifted right by the prefix.
+        let stride = |render: &Render| render.width * size_of::<u32>();
+        for y in 0..plain.height {
+            let plain_row = &plain.pixels[y * stride(&plain)..(y + 1) * stride(&plain)];
+            let labelled_row = &labelled.pixels[y * stride(&labelled)..];
+            let start = char_width * size_of::<u32>();
+            assert_eq!(plain_row, &labelled_row[start..start + plain_row.len()]);
+        }
+    }
+
+    #[test]
+    fn cells_are_reserved_for_every_digit() {
+        let plain = render_separated_number(42, 5, &RenderStyle::default());
+        let style = RenderStyle {
+            frame: Frame::Cells,
+            ..Default::default()
+        };
+        let cells = render_separated_number(42, 5, &style);
+        let inset = Frame::Cells.cell_inset();
+        assert_eq!(plain.width + 5 * 2 * inset, cells.width);
+        assert_eq!(plain.height + 2 * inset, cells.height);
+
+        // The first reserved digit has an empty cell with a bevelled edge.
+        let row = |y: usize| &cells.pixels[y * cells.width * 4..];
+        let cell = style.cell_color;
+        assert_eq!(style.background.to_bytes(), pixel_at(row(0), 0));
+        assert_eq!(cell.mix(Color::BLACK).to_bytes(), pixel_at(row(1), 1));
+        assert_eq!(cell.to_bytes(), pixel_at(row(2), 2));
+        assert_eq!(
+            cell.mix(Color::WHITE).to_bytes(),
+            pixel_at(row(2), 2 * inset + 8)
+        );
+
+        let framed = render_separated_number(
+            42,
+            5,
+            &RenderStyle {
+                frame: Frame::Framed,
+                ..style.clone()
+            },
+        );
+        let frame_width = Frame::Framed.frame_width();
+        assert_eq!(cells.width + 2 * frame_width, framed.width);
+        assert_eq!(cells.height + 2 * frame_width, framed.height);
+        let light = style.frame_color.mix(Color::WHITE);
+        assert_eq!(light.to_bytes(), pixel_at(&framed.pixels, 0));
+    }
+
+    #[test]
+    fn composite_columns_line_up() {
+        let numbers = [("Today", 12), ("Week", 80), ("Total", 12_345)];
+        let style = RenderStyle::default();
+        let row = render_composite(&numbers, 5, &style, canvas::Direction::Row);
+        let column = render_composite(&numbers, 5, &style, canvas::Direction::Column);
+
+        let total = render_separated_number(
+            12_345,
+            5,
+            &RenderStyle {
+                prefix: "Total ".into(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(total.height, row.height);
+        assert!(row.width > total.width * 2);
+
+        // Every label is padded to `Today`, so every number is the same width.
+        assert_eq!(
+            (total.width, total.height * 3),
+            (column.width, column.height)
+        );
+        let last_row = &column.pixels[total.height * 2 * column.width * 4..];
+        assert_eq!(total.pixels, last_row);
+    }
+
+    #[test]
+    fn parse_notation() {
+        assert_eq!(Notation::Full, "full".parse().unwrap());
+        assert_eq!(Notation::Compact, "compact".parse().unwrap());
+        assert!("scientific".parse::<Notation>().is_err());
+    }
+
+    #[test]
+    fn compact_numbers_round_down() {
+        let format = |number, precision| {
+            let style = RenderStyle {
+                notation: Notation::Compact,
+                precision,
+                ..Default::default()
+            };
+            match FormattedNumber::new(number, &style) {
+                FormattedNumber {
+                    integer,
+                    fraction,
+                    unit: Some(unit),
+                } => format!("{integer}.{fraction}{unit}"),
+                FormattedNumber { integer, .. } => integer,
+            }
+        };
+        assert_eq!("999", format(999, 1));
+        assert_eq!("1.0K", format(1000, 1));
+        assert_eq!("12.3K", format(12_399, 1));
+        assert_eq!("999.9K", format(999_999, 1));
+        assert_eq!("1.00M", format(1_000_000, 2));
+        assert_eq!("4.567M", format(4_567_890, 3));
+        assert_eq!("4.567M", format(4_567_890, 9));
+        assert_eq!("12.B", format(12_345_678_901, 0));
+    }
+
+    #[test]
+    fn compact_numbers_keep_the_decimal_point_in_place() {
+        let style = RenderStyle {
+            notation: Notation::Compact,
+            ..Default::default()
+        };
+        let reserve_width = style.notation.reserve_width(5);
+        assert_eq!(3, reserve_width);
+        let small = NumberLayout::new(7, reserve_width, &style);
+        let large = NumberLayout::new(12_345, reserve_width, &style);
+        assert_eq!(small.width, large.width);
+        assert!(small.text.is_empty());
+
+        // The integer digits are right-aligned before the decimal point, followed by
+        // the fraction and the unit.
+        let digits: String = large.digits.iter().map(|d| d.digit).collect();
+        assert_eq!("123K", digits);
+        assert_eq!('.', large.text[0].c);
+        assert!(large.digits[1].x < large.text[0].x && large.text[0].x < large.digits[2].x);
+        assert_eq!(small.digits[0].x, large.digits[1].x);
+
+        // Numbers too large to abbreviate further still fit.
+        let huge = NumberLayout::new(1_234_567_890_123, reserve_width, &style);
+        let digits: String = huge.digits.iter().map(|d| d.digit).collect();
+        assert_eq!("12345B", digits);
+        assert!(huge.width > large.width);
+
+        // Without a fraction, there's no decimal point either.
+        let whole = NumberLayout::new(
+            12_345,
+            reserve_width,
+            &RenderStyle {
+                precision: 0,
+                ..style.clone()
+            },
+        );
+        assert!(whole.text.is_empty());
+        let digit_advance = style.font.glyph_size().0 + style.font.kerning();
+        assert_eq!(large.digits[1].x + digit_advance, whole.digits[2].x);
+    }
+
+    #[test]
+    fn padding_is_grouped_like_digits() {
+        let style = |padding| RenderStyle {
+            padding,
+            locale: "comma".parse().unwrap(),
+            ..Default::default()
+        };
+        let zeros = render_separated_number(123, 6, &style(Padding::Zeros));
+        let number = render_separated_number(100_123, 6, &style(Padding::Zeros));
+        assert_eq!(zeros.width, number.width);
+
+        // `000,123` and `100,123` only differ in the leading digit, since the padding
+        // zeros and comma are drawn the same as the digits of a number.
+        let glyph_width = font::CLASSIC.glyph_size().0;
+        let pixels = zeros
+            .pixels
+            .chunks_exact(4)
+            .zip(number.pixels.chunks_exact(4));
+        for (index, (zero, digit)) in pixels.enumerate() {
+            if zero != digit {
+                assert!((1..1 + glyph_width).contains(&(index % zeros.width)));
+            }
+        }
+        assert_ne!(zeros.pixels, number.pixels);
+
+        let filled = render_separated_number(123, 6, &style(Padding::Fill('0')));
+        assert_eq!(zeros.pixels, filled.pixels);
+    }
+
+    #[test]
+    fn parse_padding() {
+        assert_eq!(Padding::Blank, "blank".parse().unwrap());
+        assert_eq!(Padding::Zeros, "zeros".parse().unwrap());
+        assert_eq!(Padding::Fill('*'), "*".parse().unwrap());
+        assert!("**".parse::<Padding>().is_err());
+        assert!("".parse::<Padding>().is_err());
+    }
+
+    #[test]
+    fn indexed_png_round_trip() {
+        for style in [
+            RenderStyle::default(),
+            RenderStyle {
+                background: Color::BLACK,
+                group_colors: vec![Color::rgba(0xFF, 0, 0, 0xFF), Color::rgba(0, 0, 0xFF, 0x80)],
+                frame: Frame::Framed,
+                ..Default::default()
+            },
+        ] {
+            let render = render_separated_number(1_234_567, 10, &style);
+            let png = render.to_png_bytes().unwrap();
+            assert!(png.len() < render.to_rgba_png_bytes().unwrap().len());
+
+            let mut decoder = png::Decoder::new(png.as_slice());
+            decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::ALPHA);
+            let mut reader = decoder.read_info().unwrap();
+            assert_eq!(png::ColorType::Indexed, reader.info().color_type);
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut pixels).unwrap();
+            assert_eq!(render.pixels, pixels);
+        }
+    }
+
+    #[test]
+    fn webp_round_trip() {
+        let render = render_separated_number(1_234_567, 10, &RenderStyle::default());
+        let webp = render.to_webp_bytes().unwrap();
+
+        let mut decoder = image_webp::WebPDecoder::new(io::Cursor::new(webp)).unwrap();
+        assert_eq!(
+            (render.width as u32, render.height as u32),
+            decoder.dimensions()
+        );
+        let mut pixels = vec![0; render.pixels.len()];
+        decoder.read_image(&mut pixels).unwrap();
+        assert_eq!(render.pixels, pixels);
+    }
+
+    #[test]
+    fn gif_round_trip() {
+        let style = RenderStyle {
+            group_colors: vec![Color::rgba(0xFF, 0, 0, 0xFF), Color::rgba(0, 0, 0xFF, 0x80)],
+            ..Default::default()
+        };
+        let render = render_separated_number(1_234_567, 10, &style);
+        let gif = render.to_gif_bytes().unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(gif.as_slice()).unwrap();
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!(
+            (render.width, render.height),
+            (frame.width as usize, frame.height as usize)
+        );
+        // The half transparent group becomes opaque, and the background stays transparent.
+        let expected: Vec<u8> = render
+            .pixels
+            .chunks_exact(size_of::<u32>())
+            .flat_map(|pixel| match pixel {
+                [_, _, _, 0] => [0, 0, 0, 0],
+                [r, g, b, _] => [*r, *g, *b, 0xFF],
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(expected, frame.buffer.to_vec());
+    }
+
+    #[test]
+    fn scaled_uses_nearest_neighbour() {
+        #[rustfmt::skip]
+        let render = Render {
+            width: 2,
+            height: 1,
+            pixels: vec![1, 2, 3, 4,  5, 6, 7, 8],
+        };
+        let scaled = render.scaled(2);
+        assert_eq!((4, 2), (scaled.width, scaled.height));
+        #[rustfmt::skip]
+        assert_eq!(
+            vec![
+                1, 2, 3, 4,  1, 2, 3, 4,  5, 6, 7, 8,  5, 6, 7, 8,
+                1, 2, 3, 4,  1, 2, 3, 4,  5, 6, 7, 8,  5, 6, 7, 8,
+            ],
+            scaled.pixels
+        );
+    }
+
+    #[test]
+    fn scale_is_clamped() {
+        let unscaled = render_separated_number(1, 1, &RenderStyle::default());
+        let style = RenderStyle {
+            scale: 100,
+            ..Default::default()
+        };
+        let render = render_separated_number(1, 1, &style);
+        assert_eq!(unscaled.width * MAX_SCALE, render.width);
+        assert_eq!(unscaled.height * MAX_SCALE, render.height);
+    }
+
+    #[test]
+    fn render_with_each_builtin_font() {
+        for name in font::BUILTIN_FONT_NAMES {
+            let style = RenderStyle {
+                font: font::builtin(name).unwrap(),
+                ..Default::default()
+            };
+            let (glyph_width, glyph_height) = style.font.glyph_size();
+            let render = render_separated_number(123, 3, &style);
+            assert_eq!(glyph_height + 2, render.height, "{name}");
+            assert!(render.width > glyph_width * 3, "{name}");
+            assert!(render.pixels.chunks(4).any(|pixel| pixel[3] != 0), "{name}");
+        }
+    }
+
+    #[test]
+    fn reused_buffer_matches_new_renders() {
+        let styles = [
+            RenderStyle::default(),
+            RenderStyle {
+                frame: Frame::Framed,
+                scale: 3,
+                prefix: "Visitors: ".into(),
+                ..Default::default()
+            },
+            RenderStyle {
+                notation: Notation::Compact,
+                locale: "comma".parse().unwrap(),
+                scale: 2,
+                ..Default::default()
+            },
+            RenderStyle {
+                font: font::builtin("lcd").unwrap(),
+                ..Default::default()
+            },
+        ];
+        // Alternate between larger and smaller renders so that anything left over shows up.
+        let mut buffer = RenderBuffer::new();
+        for style in &styles {
+            for number in [1_234_567_890, 7, 12_345] {
+                let new = render_separated_number(number, 5, style);
+                let reused = render_separated_number_into(number, 5, style, &mut buffer);
+                assert_eq!((new.width, new.height), (reused.width, reused.height));
+                assert!(new.pixels == reused.pixels, "{number}");
+            }
+        }
+    }
+
+    #[test]
+    fn reused_png_writer_matches_to_png_bytes() {
+        // Too many colors for a palette, so this is written as an RGBA image.
+        let colorful = Render {
+            width: 300,
+            height: 1,
+            pixels: (0..300u32)
+                .flat_map(|i| [i as u8, (i >> 8) as u8, 0, 0xFF])
+                .collect(),
+        };
+        let framed = RenderStyle {
+            frame: Frame::Framed,
+            ..Default::default()
+        };
+        let renders = [
+            colorful,
+            render_separated_number(1_234_567_890, 10, &framed),
+            render_separated_number(7, 1, &RenderStyle::default()),
+        ];
+        let mut writer = PngWriter::new();
+        let mut png = Vec::new();
+        for render in &renders {
+            png.clear();
+            writer.write(render, &mut png).unwrap();
+            assert_eq!(render.to_png_bytes().unwrap(), png);
+        }
+    }
+
+    #[test]
+    fn full_leading_group_has_no_group_space() {
+        let style = RenderStyle::default();
+        let width =
+            |number, reserve_width| render_separated_number(number, reserve_width, &style).width;
+        // 2px padding, 9px per digit including kerning, and 3px between groups.
+        assert_eq!(2 + 3 * 9, width(123, 1));
+        assert_eq!(2 + 3 * 9, width(123, 3));
+        assert_eq!(2 + 6 * 9 + 3, width(123_456, 1));
+        assert_eq!(2 + 6 * 9 + 3, width(123_456, 6));
+        assert_eq!(2 + 6 * 9 + 3, width(123, 6));
+
+        // A short count stays right-aligned with the last group of a longer one.
+        let right_columns = |render: Render| -> Vec<u8> {
+            let columns = 1 + 3 * 9;
+            render
+                .pixels
+                .chunks(render.width * 4)
+                .flat_map(|row| row[row.len() - columns * 4..].to_vec())
+                .collect()
+        };
+        assert_eq!(
+            right_columns(render_separated_number(123_123, 6, &style)),
+            right_columns(render_separated_number(123, 6, &style))
+        );
+    }
 }
@@ -0,0 +1,328 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Animated counter rendering.
+
+use super::{FormattedNumber, NumberLayout, Render, RenderStyle};
+use std::mem::size_of;
+
+/// How long the previous count is shown before its digits start rolling.
+const HOLD_DELAY_MS: u16 = 500;
+/// How long each in-between frame of the roll is shown.
+const ROLL_DELAY_MS: u16 = 40;
+/// Number of in-between frames used to roll a digit to its new value.
+const ROLL_FRAMES: usize = 8;
+
+/// A single frame of an [`Animation`].
+pub struct AnimationFrame {
+    /// The frame's image.
+    pub render: Render,
+    /// How long the frame is shown before moving on to the next frame.
+    pub delay_ms: u16,
+}
+
+/// A sequence of renders that all have the same size.
+pub struct Animation {
+    /// Frames in the order they are shown.
+    pub frames: Vec<AnimationFrame>,
+}
+
+impl Animation {
+    /// Convert this animation to an in-memory animated PNG (APNG) image.
+    ///
+    /// The animation plays once and then stays on its last frame. The last frame is
+    /// also written as the default image so that viewers without APNG support show it.
+    pub fn to_apng_bytes(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let last = &self
+            .frames
+            .last()
+            .expect("animations have at least one frame")
+            .render;
+        let mut png: Vec<u8> = Vec::with_capacity(last.pixels.len());
+
+        let mut encoder = png::Encoder::new(&mut png, last.width as u32, last.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as u32, 1)?;
+        encoder.set_sep_def_img(true)?;
+        let mut encoder = encoder.write_header()?;
+        encoder.write_image_data(&last.pixels)?;
+        for frame in &self.frames {
+            encoder.set_frame_delay(frame.delay_ms, 1000)?;
+            encoder.write_image_data(&frame.render.pixels)?;
+        }
+        encoder.finish()?;
+        Ok(png)
+    }
+}
+
+/// Render an odometer animation where the digits that differ between `from` and `to`
+/// roll upward from their old value to their new one.
+///
+/// The `reserve_width` works the same as in [`render_separated_number`](super::render_separated_number),
+/// except that it is widened to fit both numbers so that every frame has the same size.
+pub fn render_odometer(
+    from: usize,
+    to: usize,
+    reserve_width: usize,
+    style: &RenderStyle,
+) -> Animation {
+    let digit_count = |number| FormattedNumber::new(number, style).integer.len();
+    let reserve_width = style
+        .notation
+        .reserve_width(reserve_width)
+        .max(digit_count(from))
+        .max(digit_count(to));
+    let from_layout = NumberLayout::new(from, reserve_width, style);
+    let to_layout = NumberLayout::new(to, reserve_width, style);
+    let from_render = from_layout.render(style);
+    let to_render = to_layout.render(style);
+
+    let rolling = rolling_digits(&from_layout, &to_layout);
+    if rolling.is_empty() {
+        return Animation {
+            frames: vec![AnimationFrame {
+                render: to_render.scaled(style.scale),
+                delay_ms: 0,
+            }],
+        };
+    }
+
+    let glyph_size = style.font.glyph_size();
+    let mut frames = Vec::with_capacity(ROLL_FRAMES + 2);
+    frames.push(AnimationFrame {
+        render: from_render.clone().scaled(style.scale),
+        delay_ms: HOLD_DELAY_MS,
+    });
+    for step in 1..=ROLL_FRAMES {
+        let offset = glyph_size.1 * step / (ROLL_FRAMES + 1);
+        let mut pixels = to_render.pixels.clone();
+        for &x in &rolling {
+            roll_digit_into(
+                &mut pixels,
+                &from_render,
+                &to_render,
+                (x, to_layout.y),
+                glyph_size,
+                offset,
+            );
+        }
+        let render = Render {
+            width: to_render.width,
+            height: to_render.height,
+            pixels,
+        };
+        frames.push(AnimationFrame {
+            render: render.scaled(style.scale),
+            delay_ms: ROLL_DELAY_MS,
+        });
+    }
+    frames.push(AnimationFrame {
+        render: to_render.scaled(style.scale),
+        delay_ms: 0,
+    });
+    Animation { frames }
+}
+
+/// Returns the X offsets of the digits in `to` that change from `from`.
+///
+/// Digits are matched by position, since compact numbers move digits around their
+/// decimal point and unit when their shape changes, such as from `999` to `1.0K`.
+/// Digits without a digit in the same position before are always rolled.
+fn rolling_digits(from: &NumberLayout, to: &NumberLayout) -> Vec<usize> {
+    to.digits
+        .iter()
+        .filter(|to_digit| {
+            from.digits
+                .iter()
+                .find(|from_digit| from_digit.x == to_digit.x)
+                .map(|from_digit| from_digit.digit != to_digit.digit)
+                .unwrap_or(true)
+        })
+        .map(|to_digit| to_digit.x)
+        .collect()
+}
+
+/// Copy a digit that is `offset` rows into its roll into `pixels`.
+///
+/// The top of the digit cell shows the bottom of the old digit from `from`, and the
+/// bottom of the cell shows the top of the new digit from `to`.
+fn roll_digit_into(
+    pixels: &mut [u8],
+    from: &Render,
+    to: &Render,
+    (x, y): (usize, usize),
+    (glyph_width, glyph_height): (usize, usize),
+    offset: usize,
+) {
+    let stride = to.width * size_of::<u32>();
+    let columns = x * size_of::<u32>()..(x + glyph_width) * size_of::<u32>();
+    for row in 0..glyph_height {
+        let source_row = row + offset;
+        let (source, source_y) = if source_row < glyph_height {
+            (from, y + source_row)
+        } else {
+            (to, y + source_row - glyph_height)
+        };
+        let dest = (y + row) * stride;
+        let src = source_y * stride;
+        pixels[dest + columns.start..dest + columns.end]
+            .copy_from_slice(&source.pixels[src + columns.start..src + columns.end]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::render_separated_number;
+
+    #[test]
+    fn compact_frames_have_the_same_size() {
+        let style = RenderStyle {
+            notation: crate::counter::Notation::Compact,
+            ..Default::default()
+        };
+        // The number before the decimal point gets longer than compact numbers usually are.
+        let animation = render_odometer(999_999_999_999, 1_000_000_000_000, 5, &style);
+        let last = &animation.frames[animation.frames.len() - 1].render;
+        for frame in &animation.frames {
+            assert_eq!(
+                (last.width, last.height),
+                (frame.render.width, frame.render.height)
+            );
+        }
+    }
+
+    #[test]
+    fn frames_start_and_end_on_the_counts() {
+        let style = RenderStyle::default();
+        let animation = render_odometer(1299, 1300, 5, &style);
+        assert_eq!(ROLL_FRAMES + 2, animation.frames.len());
+
+        let first = &animation.frames[0].render;
+        let last = &animation.frames[animation.frames.len() - 1].render;
+        assert_eq!(
+            render_separated_number(1299, 5, &style).pixels,
+            first.pixels
+        );
+        assert_eq!(render_separated_number(1300, 5, &style).pixels, last.pixels);
+        for frame in &animation.frames {
+            assert_eq!(
+                (first.width, first.height),
+                (frame.render.width, frame.render.height)
+            );
+        }
+    }
+
+    #[test]
+    fn unchanged_digits_do_not_roll() {
+        let style = RenderStyle::default();
+        let animation = render_odometer(1299, 1300, 4, &style);
+        let to = render_separated_number(1300, 4, &style);
+
+        // The leading "1" at X offset 1 doesn't change, so its columns are the same in every frame.
+        let stride = to.width * size_of::<u32>();
+        let glyph_width = style.font.glyph_size().0;
+        let leading = size_of::<u32>()..(1 + glyph_width) * size_of::<u32>();
+        for frame in &animation.frames {
+            for y in 0..to.height {
+                let row = y * stride;
+                assert_eq!(
+                    &to.pixels[row + leading.start..row + leading.end],
+                    &frame.render.pixels[row + leading.start..row + leading.end],
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compact_digits_roll_by_position() {
+        let style = RenderStyle {
+            notation: crate::counter::Notation::Compact,
+            ..Default::default()
+        };
+        let rolling = |from, to| {
+            let from = NumberLayout::new(from, 3, &style);
+            let to_layout = NumberLayout::new(to, 3, &style);
+            let all: Vec<usize> = to_layout.digits.iter().map(|d| d.x).collect();
+            (rolling_digits(&from, &to_layout), all)
+        };
+        let text = |number| {
+            let formatted = FormattedNumber::new(number, &style);
+            let mut text = formatted.integer;
+            if !formatted.fraction.is_empty() {
+                text.push('.');
+                text.push_str(&formatted.fraction);
+            }
+            text.extend(formatted.unit);
+            text
+        };
+
+        // Matched from the right, the "1" and "0" would look unchanged, but every digit
+        // has moved or is new.
+        assert_eq!(["100", "1.0K"], [text(100), text(1_000)]);
+        let (rolling_xs, all) = rolling(100, 1_000);
+        assert_eq!(all, rolling_xs);
+
+        // Every digit moves, but the unit stays put.
+        assert_eq!(["9.9K", "10.0K"], [text(9_900), text(10_000)]);
+        let (rolling_xs, all) = rolling(9_900, 10_000);
+        assert_eq!(all[..all.len() - 1], rolling_xs);
+
+        // Only the changed digit rolls when the shape stays the same.
+        assert_eq!(["1.2K", "1.3K"], [text(1_200), text(1_300)]);
+        let (rolling_xs, all) = rolling(1_200, 1_300);
+        assert_eq!(vec![all[1]], rolling_xs);
+    }
+
+    #[test]
+    fn same_count_is_a_single_frame() {
+        let animation = render_odometer(42, 42, 5, &RenderStyle::default());
+        assert_eq!(1, animation.frames.len());
+    }
+
+    #[test]
+    fn growing_number_keeps_frame_size() {
+        let animation = render_odometer(999, 1000, 1, &RenderStyle::default());
+        let size = |frame: &AnimationFrame| (frame.render.width, frame.render.height);
+        assert!(animation
+            .frames
+            .iter()
+            .all(|frame| size(frame) == size(&animation.frames[0])));
+    }
+
+    #[test]
+    fn apng_round_trip() {
+        let style = RenderStyle {
+            scale: 2,
+            ..Default::default()
+        };
+        let animation = render_odometer(9, 10, 5, &style);
+        let apng = animation.to_apng_bytes().unwrap();
+
+        let decoder = png::Decoder::new(apng.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let control = reader.info().animation_control().unwrap();
+        assert_eq!(animation.frames.len() as u32, control.num_frames);
+        assert_eq!(1, control.num_plays);
+
+        // The default image is the final count.
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buffer).unwrap();
+        assert_eq!(render_separated_number(10, 5, &style).pixels, buffer);
+    }
+}
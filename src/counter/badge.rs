@@ -0,0 +1,466 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Badges in the style of [shields.io](https://shields.io), with a label on the left
+//! and the count on the right.
+
+use super::{Color, Grouping, Locale, ParseColorError, Separator};
+use std::{
+    error::Error as StdError,
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+/// Widths of the printable ASCII characters from space to `~` in Verdana, in font units
+/// out of 2048 per em, which is the font that badges are sized for.
+#[rustfmt::skip]
+static VERDANA_WIDTHS: [u16; 95] = [
+    720, 807, 942, 1677, 1302, 2213, 1473, 550, 930, 930, 1302, 1677, 745, 883, 745, 930,
+    1302, 1302, 1302, 1302, 1302, 1302, 1302, 1302, 1302, 1302, 930, 930, 1677, 1677, 1677, 1110,
+    2048, 1401, 1405, 1430, 1577, 1294, 1178, 1587, 1540, 868, 1015, 1426, 1141, 1729, 1532, 1612,
+    1235, 1612, 1423, 1401, 1245, 1499, 1401, 2032, 1403, 1237, 1403, 930, 930, 930, 1677, 1302,
+    1302, 1229, 1276, 1067, 1276, 1220, 720, 1276, 1296, 562, 614, 1184, 562, 1992, 1296, 1243,
+    1276, 1276, 874, 1067, 807, 1296, 1184, 1675, 1184, 1184, 1051, 1302, 930, 1302, 1677,
+];
+/// Width used for characters that aren't in [`VERDANA_WIDTHS`], which is the width of a digit.
+const FALLBACK_WIDTH: u16 = 1302;
+/// How much wider bold Verdana is than regular Verdana, on average.
+const BOLD_FACTOR: f32 = 1.1;
+
+/// Named colors supported by shields.io.
+const NAMED_COLORS: [(&str, Color); 10] = [
+    ("brightgreen", Color::rgba(0x44, 0xCC, 0x11, 0xFF)),
+    ("green", Color::rgba(0x97, 0xCA, 0x00, 0xFF)),
+    ("yellowgreen", Color::rgba(0xA4, 0xA6, 0x1D, 0xFF)),
+    ("yellow", Color::rgba(0xDF, 0xB3, 0x17, 0xFF)),
+    ("orange", Color::rgba(0xFE, 0x7D, 0x37, 0xFF)),
+    ("red", Color::rgba(0xE0, 0x5D, 0x44, 0xFF)),
+    ("blue", Color::rgba(0x00, 0x7E, 0xC6, 0xFF)),
+    ("grey", Color::rgba(0x55, 0x55, 0x55, 0xFF)),
+    ("gray", Color::rgba(0x55, 0x55, 0x55, 0xFF)),
+    ("lightgrey", Color::rgba(0x9F, 0x9F, 0x9F, 0xFF)),
+];
+
+/// Parse a badge color, which is either one of the shields.io color names, such as
+/// `brightgreen`, or a hex color in the same formats as [`Color`].
+pub fn parse_badge_color(value: &str) -> Result<Color, ParseColorError> {
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| *name == value)
+        .map(|&(_, color)| Ok(color))
+        .unwrap_or_else(|| value.parse())
+}
+
+/// The overall look of a badge.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BadgeShape {
+    /// Rounded corners with a subtle gradient and text shadow.
+    #[default]
+    Flat,
+    /// Square corners without a gradient.
+    FlatSquare,
+    /// A taller badge with square corners and uppercase text.
+    ForTheBadge,
+}
+
+/// An error parsing a [`BadgeShape`] from a string.
+#[derive(Debug)]
+pub struct ParseBadgeShapeError(String);
+
+impl StdError for ParseBadgeShapeError {}
+
+impl fmt::Display for ParseBadgeShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown badge style `{}` (expected one of: flat, flat-square, for-the-badge)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for BadgeShape {
+    type Err = ParseBadgeShapeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "flat" => Ok(BadgeShape::Flat),
+            "flat-square" => Ok(BadgeShape::FlatSquare),
+            "for-the-badge" => Ok(BadgeShape::ForTheBadge),
+            _ => Err(ParseBadgeShapeError(value.into())),
+        }
+    }
+}
+
+impl BadgeShape {
+    /// Height of the badge in pixels.
+    fn height(&self) -> usize {
+        match self {
+            BadgeShape::Flat | BadgeShape::FlatSquare => 20,
+            BadgeShape::ForTheBadge => 28,
+        }
+    }
+
+    /// Space on either side of each part's text in pixels.
+    fn padding(&self) -> usize {
+        match self {
+            BadgeShape::Flat | BadgeShape::FlatSquare => 5,
+            BadgeShape::ForTheBadge => 9,
+        }
+    }
+
+    /// Font size in pixels.
+    fn font_size(&self) -> usize {
+        match self {
+            BadgeShape::Flat | BadgeShape::FlatSquare => 11,
+            BadgeShape::ForTheBadge => 10,
+        }
+    }
+
+    /// Text baseline from the top of the badge in pixels.
+    fn baseline(&self) -> f32 {
+        match self {
+            BadgeShape::Flat | BadgeShape::FlatSquare => 14.0,
+            BadgeShape::ForTheBadge => 17.5,
+        }
+    }
+}
+
+/// Style options for a badge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BadgeStyle {
+    /// Text on the left side of the badge, which is left out if empty.
+    pub label: String,
+    /// Background color of the label.
+    pub label_color: Color,
+    /// Background color of the count.
+    pub color: Color,
+    /// The overall look of the badge.
+    pub shape: BadgeShape,
+    /// How the count is grouped and separated.
+    pub locale: Locale,
+}
+
+impl Default for BadgeStyle {
+    fn default() -> Self {
+        Self {
+            label: "visitors".into(),
+            label_color: parse_badge_color("grey").expect("named color"),
+            color: parse_badge_color("blue").expect("named color"),
+            shape: BadgeShape::default(),
+            locale: Locale {
+                grouping: Grouping::Thousands,
+                separator: Separator::Comma,
+            },
+        }
+    }
+}
+
+/// One side of a badge.
+struct BadgePart {
+    text: String,
+    color: Color,
+    /// Width of the text in pixels.
+    text_width: usize,
+    /// Width of the part, including padding, in pixels.
+    width: usize,
+    bold: bool,
+}
+
+impl BadgePart {
+    fn new(text: String, color: Color, shape: BadgeShape, bold: bool) -> Self {
+        let text = match shape {
+            BadgeShape::ForTheBadge => text.to_uppercase(),
+            _ => text,
+        };
+        let text_width = text_width(&text, shape, bold);
+        Self {
+            text,
+            color,
+            text_width,
+            width: text_width + shape.padding() * 2,
+            bold,
+        }
+    }
+}
+
+/// Render a badge for a count as an SVG image.
+///
+/// The text is sized with the widths of Verdana and rendered with `textLength` so that it
+/// fits its part of the badge even when the viewer falls back to a different font.
+pub fn render_badge(number: usize, style: &BadgeStyle) -> String {
+    let shape = style.shape;
+    let label = (!style.label.is_empty())
+        .then(|| BadgePart::new(style.label.clone(), style.label_color, shape, false));
+    let message = BadgePart::new(
+        style.locale.format(number),
+        style.color,
+        shape,
+        shape == BadgeShape::ForTheBadge,
+    );
+    let label_width = label.as_ref().map(|label| label.width).unwrap_or(0);
+    let width = label_width + message.width;
+    let height = shape.height();
+
+    let title = match &label {
+        Some(label) => format!("{}: {}", escape_xml(&label.text), escape_xml(&message.text)),
+        None => escape_xml(&message.text),
+    };
+    let mut svg = String::with_capacity(1024);
+    write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         role=\"img\" aria-label=\"{title}\"><title>{title}</title>"
+    )
+    .unwrap();
+
+    let flat = shape == BadgeShape::Flat;
+    if flat {
+        write!(
+            svg,
+            "<linearGradient id=\"s\" x2=\"0\" y2=\"100%\">\
+             <stop offset=\"0\" stop-color=\"#bbb\" stop-opacity=\".1\"/>\
+             <stop offset=\"1\" stop-opacity=\".1\"/></linearGradient>\
+             <clipPath id=\"r\"><rect width=\"{width}\" height=\"{height}\" rx=\"3\" fill=\"#fff\"/>\
+             </clipPath><g clip-path=\"url(#r)\">"
+        )
+        .unwrap();
+    } else {
+        svg.push_str("<g shape-rendering=\"crispEdges\">");
+    }
+    if let Some(label) = &label {
+        write!(
+            svg,
+            "<rect width=\"{}\" height=\"{height}\"{}/>",
+            label.width,
+            fill(label.color)
+        )
+        .unwrap();
+    }
+    write!(
+        svg,
+        "<rect x=\"{label_width}\" width=\"{}\" height=\"{height}\"{}/>",
+        message.width,
+        fill(message.color)
+    )
+    .unwrap();
+    if flat {
+        write!(
+            svg,
+            "<rect width=\"{width}\" height=\"{height}\" fill=\"url(#s)\"/>"
+        )
+        .unwrap();
+    }
+
+    // Text is written at ten times the size and scaled down, which positions it more precisely
+    // than whole pixels without needing fractional coordinates.
+    write!(
+        svg,
+        "</g><g fill=\"#fff\" text-anchor=\"middle\" \
+         font-family=\"Verdana,Geneva,DejaVu Sans,sans-serif\" \
+         text-rendering=\"geometricPrecision\" font-size=\"{}\">",
+        shape.font_size() * 10
+    )
+    .unwrap();
+    let parts = label
+        .iter()
+        .map(|label| (0, label))
+        .chain([(label_width, &message)]);
+    for (x, part) in parts {
+        let center = x * 10 + part.width * 5;
+        let baseline = (shape.baseline() * 10.0) as usize;
+        let weight = if part.bold {
+            " font-weight=\"bold\""
+        } else {
+            ""
+        };
+        let (text_color, shadow_color) = if is_light(part.color) {
+            ("#333", "#ccc")
+        } else {
+            ("#fff", "#010101")
+        };
+        let text = escape_xml(&part.text);
+        if flat {
+            write!(
+                svg,
+                "<text aria-hidden=\"true\" x=\"{center}\" y=\"{}\" fill=\"{shadow_color}\" \
+                 fill-opacity=\".3\" transform=\"scale(.1)\" textLength=\"{}\"{weight}>{text}</text>",
+                baseline + 10,
+                part.text_width * 10
+            )
+            .unwrap();
+        }
+        write!(
+            svg,
+            "<text x=\"{center}\" y=\"{baseline}\" transform=\"scale(.1)\" fill=\"{text_color}\" \
+             textLength=\"{}\"{weight}>{text}</text>",
+            part.text_width * 10
+        )
+        .unwrap();
+    }
+    svg.push_str("</g></svg>");
+    svg
+}
+
+/// Returns the approximate width of some text in pixels.
+fn text_width(text: &str, shape: BadgeShape, bold: bool) -> usize {
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            let index = (c as usize).wrapping_sub(' ' as usize);
+            VERDANA_WIDTHS.get(index).copied().unwrap_or(FALLBACK_WIDTH) as u32
+        })
+        .sum();
+    let mut width = units as f32 * shape.font_size() as f32 / 2048.0;
+    if bold {
+        width *= BOLD_FACTOR;
+    }
+    if shape == BadgeShape::ForTheBadge {
+        // The uppercase text is spaced out a bit more.
+        width += 1.25 * text.chars().count() as f32;
+    }
+    width.round() as usize
+}
+
+/// Returns true if dark text is easier to read than white text on the given color.
+fn is_light(color: Color) -> bool {
+    let brightness = 0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32;
+    brightness / 255.0 > 0.69
+}
+
+/// Returns the `fill` attribute for a color, including its opacity if it has one.
+fn fill(color: Color) -> String {
+    let Color { r, g, b, a } = color;
+    let mut fill = format!(" fill=\"#{r:02x}{g:02x}{b:02x}\"");
+    if a != 0xFF {
+        write!(fill, " fill-opacity=\"{:.3}\"", a as f32 / 255.0).unwrap();
+    }
+    fill
+}
+
+/// Escape text for use in XML content and attribute values.
+///
+/// Control characters are dropped, since most of them aren't allowed in XML at all.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_badge_colors() {
+        assert_eq!(
+            Color::rgba(0x44, 0xCC, 0x11, 0xFF),
+            parse_badge_color("brightgreen").unwrap()
+        );
+        assert_eq!(
+            Color::rgba(0x12, 0x34, 0x56, 0xFF),
+            parse_badge_color("123456").unwrap()
+        );
+        assert!(parse_badge_color("chartreuse").is_err());
+    }
+
+    #[test]
+    fn parse_badge_shape() {
+        assert_eq!(BadgeShape::Flat, "flat".parse().unwrap());
+        assert_eq!(BadgeShape::FlatSquare, "flat-square".parse().unwrap());
+        assert_eq!(BadgeShape::ForTheBadge, "for-the-badge".parse().unwrap());
+        assert!("plastic".parse::<BadgeShape>().is_err());
+    }
+
+    #[test]
+    fn text_widths() {
+        // Digits are 1302/2048 of the font size.
+        assert_eq!(35, text_width("12345", BadgeShape::Flat, false));
+        assert_eq!(40, text_width("visitors", BadgeShape::Flat, false));
+        // Non-ASCII characters are given the width of a digit.
+        assert_eq!(7, text_width("\u{2009}", BadgeShape::Flat, false));
+    }
+
+    #[test]
+    fn flat_badge() {
+        let svg = render_badge(12_345, &BadgeStyle::default());
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"99\" height=\"20\" \
+             role=\"img\" aria-label=\"visitors: 12,345\">"
+        ));
+        // The label is 40px wide plus padding, and the count starts after it.
+        assert!(svg.contains("<rect width=\"50\" height=\"20\" fill=\"#555555\"/>"));
+        assert!(svg.contains("<rect x=\"50\" width=\"49\" height=\"20\" fill=\"#007ec6\"/>"));
+        assert!(svg.contains(
+            "<text x=\"745\" y=\"140\" transform=\"scale(.1)\" fill=\"#fff\" \
+             textLength=\"390\">12,345</text>"
+        ));
+        assert!(svg.contains("url(#s)"));
+    }
+
+    #[test]
+    fn badge_without_label() {
+        let style = BadgeStyle {
+            label: String::new(),
+            color: parse_badge_color("ffd700").unwrap(),
+            shape: BadgeShape::FlatSquare,
+            ..Default::default()
+        };
+        let svg = render_badge(7, &style);
+        assert!(svg.contains("aria-label=\"7\""));
+        assert!(svg.contains("<rect x=\"0\" width=\"17\" height=\"20\" fill=\"#ffd700\"/>"));
+        // Gold is light enough to need dark text, and square badges have no shadow.
+        assert!(svg.contains("fill=\"#333\""));
+        assert!(!svg.contains("aria-hidden"));
+        assert!(!svg.contains("url(#s)"));
+    }
+
+    #[test]
+    fn for_the_badge_is_uppercase() {
+        let style = BadgeStyle {
+            label: "Hits <3".into(),
+            shape: BadgeShape::ForTheBadge,
+            ..Default::default()
+        };
+        let svg = render_badge(1000, &style);
+        assert!(svg.contains("height=\"28\""));
+        assert!(svg.contains(">HITS &lt;3</text>"));
+        assert!(svg.contains("font-weight=\"bold\">1,000</text>"));
+    }
+
+    #[test]
+    fn control_characters_are_dropped() {
+        assert_eq!("a&amp;b", escape_xml("a\u{1}&\u{7f}b\n"));
+        let style = BadgeStyle {
+            label: "\u{1}hits".into(),
+            ..Default::default()
+        };
+        let svg = render_badge(7, &style);
+        assert!(!svg.contains('\u{1}'));
+        assert!(svg.contains(">hits</text>"));
+    }
+}
@@ -0,0 +1,324 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A small drawing API for composing several renders and labels into a single image,
+//! such as a widget that shows more than one number.
+
+use super::{
+    fill_rect,
+    font::{self, BitmapFont},
+    Color, Render,
+};
+use std::{error::Error as StdError, fmt, mem::size_of, str::FromStr};
+
+/// A fixed-size image that can be drawn on, and then turned into a [`Render`].
+///
+/// Everything drawn is clipped to the canvas.
+pub struct Canvas {
+    render: Render,
+}
+
+impl Canvas {
+    /// Creates a canvas filled with the background color.
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        Self {
+            render: Render {
+                width,
+                height,
+                pixels: background.to_bytes().repeat(width * height),
+            },
+        }
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> usize {
+        self.render.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> usize {
+        self.render.height
+    }
+
+    /// Fill a rectangle with a color, replacing what was there.
+    pub fn fill_rect(
+        &mut self,
+        (x, y): (usize, usize),
+        (width, height): (usize, usize),
+        color: Color,
+    ) {
+        let width = width.min(self.width().saturating_sub(x));
+        let height = height.min(self.height().saturating_sub(y));
+        if width > 0 && height > 0 {
+            fill_rect(
+                &mut self.render.pixels,
+                self.render.width,
+                (x, y),
+                (width, height),
+                color,
+            );
+        }
+    }
+
+    /// Draw text with its top left corner at `(x, y)`, and return its width in pixels.
+    ///
+    /// Characters are spaced the same as the labels of a counter, and characters that
+    /// the font doesn't have are left blank.
+    pub fn draw_text(
+        &mut self,
+        (mut x, y): (usize, usize),
+        text: &str,
+        font: &dyn BitmapFont,
+        color: Color,
+    ) -> usize {
+        let start = x;
+        for c in text.chars() {
+            // Glyphs are clipped to the buffer by the blit.
+            font::blit_char_into(
+                font,
+                &mut self.render.pixels,
+                self.render.width,
+                c,
+                color,
+                x,
+                y,
+            );
+            x += char_advance(font, c);
+        }
+        x - start
+    }
+
+    /// Draw a render with its top left corner at `(x, y)`, blending its translucent
+    /// pixels over what was there.
+    pub fn draw_render(&mut self, (x, y): (usize, usize), render: &Render) {
+        let columns = render.width.min(self.width().saturating_sub(x));
+        let rows = render.height.min(self.height().saturating_sub(y));
+        if columns == 0 {
+            return;
+        }
+        let source_stride = render.width * size_of::<u32>();
+        let dest_stride = self.render.width * size_of::<u32>();
+        for row in 0..rows {
+            let source = &render.pixels[row * source_stride..][..columns * size_of::<u32>()];
+            let dest_start = (y + row) * dest_stride + x * size_of::<u32>();
+            let dest = &mut self.render.pixels[dest_start..][..columns * size_of::<u32>()];
+            for (dest, source) in dest
+                .chunks_exact_mut(size_of::<u32>())
+                .zip(source.chunks_exact(size_of::<u32>()))
+            {
+                blend_over(dest, source);
+            }
+        }
+    }
+
+    /// Finish drawing and return the image.
+    pub fn into_render(self) -> Render {
+        self.render
+    }
+}
+
+/// Returns how far [`Canvas::draw_text`] moves to the right for a character.
+pub(super) fn char_advance(font: &dyn BitmapFont, c: char) -> usize {
+    font.glyph(c)
+        .map(|glyph| glyph.width)
+        .unwrap_or(font.glyph_size().0)
+        + font.kerning()
+}
+
+/// Returns the width of text in pixels when it's drawn with [`Canvas::draw_text`].
+pub fn text_width(font: &dyn BitmapFont, text: &str) -> usize {
+    text.chars().map(|c| char_advance(font, c)).sum()
+}
+
+/// Blend a source pixel over a destination pixel, both in non-premultiplied RGBA.
+fn blend_over(dest: &mut [u8], source: &[u8]) {
+    match source[3] {
+        0 => {}
+        0xFF => dest.copy_from_slice(source),
+        source_alpha => {
+            let source_alpha = source_alpha as u32;
+            let dest_alpha = dest[3] as u32 * (0xFF - source_alpha) / 0xFF;
+            let alpha = source_alpha + dest_alpha;
+            for channel in 0..3 {
+                dest[channel] = ((source[channel] as u32 * source_alpha
+                    + dest[channel] as u32 * dest_alpha)
+                    / alpha) as u8;
+            }
+            dest[3] = alpha as u8;
+        }
+    }
+}
+
+/// Which way [`stack`] places renders.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Left to right, centered vertically.
+    #[default]
+    Row,
+    /// Top to bottom, aligned on the left.
+    Column,
+}
+
+/// An error parsing a [`Direction`] from a string.
+#[derive(Debug)]
+pub struct ParseDirectionError(String);
+
+impl StdError for ParseDirectionError {}
+
+impl fmt::Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown direction `{}` (expected one of: row, column)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "row" => Ok(Direction::Row),
+            "column" => Ok(Direction::Column),
+            _ => Err(ParseDirectionError(value.into())),
+        }
+    }
+}
+
+/// Compose renders into a single render by placing them one after the other,
+/// with `gap` pixels of the background color between each of them.
+pub fn stack(renders: &[Render], direction: Direction, gap: usize, background: Color) -> Render {
+    let gaps = gap * renders.len().saturating_sub(1);
+    let (width, height) = match direction {
+        Direction::Row => (
+            renders.iter().map(|r| r.width).sum::<usize>() + gaps,
+            renders.iter().map(|r| r.height).max().unwrap_or(0),
+        ),
+        Direction::Column => (
+            renders.iter().map(|r| r.width).max().unwrap_or(0),
+            renders.iter().map(|r| r.height).sum::<usize>() + gaps,
+        ),
+    };
+
+    let mut canvas = Canvas::new(width, height, background);
+    let mut offset = 0;
+    for render in renders {
+        match direction {
+            Direction::Row => {
+                canvas.draw_render((offset, (height - render.height) / 2), render);
+                offset += render.width + gap;
+            }
+            Direction::Column => {
+                canvas.draw_render((0, offset), render);
+                offset += render.height + gap;
+            }
+        }
+    }
+    canvas.into_render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::{pixel_at, render_separated_number, RenderStyle};
+
+    const RED: Color = Color::rgba(0xFF, 0, 0, 0xFF);
+
+    fn solid(width: usize, height: usize, color: Color) -> Render {
+        Canvas::new(width, height, color).into_render()
+    }
+
+    #[test]
+    fn fill_rect_is_clipped() {
+        let mut canvas = Canvas::new(4, 3, Color::TRANSPARENT);
+        canvas.fill_rect((2, 1), (10, 10), RED);
+        canvas.fill_rect((10, 10), (1, 1), RED);
+        let render = canvas.into_render();
+        let row = |y: usize| &render.pixels[y * render.width * 4..];
+        assert_eq!(Color::TRANSPARENT.to_bytes(), pixel_at(row(1), 1));
+        assert_eq!(RED.to_bytes(), pixel_at(row(1), 2));
+        assert_eq!(RED.to_bytes(), pixel_at(row(2), 3));
+    }
+
+    #[test]
+    fn text_matches_counter_labels() {
+        let style = RenderStyle {
+            prefix: "Hi ".into(),
+            ..Default::default()
+        };
+        let counter = render_separated_number(7, 1, &style);
+        let font = &*style.font;
+
+        // Draw the same label and digit where the counter has them, which is inside its 1px margin.
+        let mut canvas = Canvas::new(counter.width, counter.height, style.background);
+        let width = canvas.draw_text((1, 1), "Hi 7", font, style.foreground);
+        assert_eq!(text_width(font, "Hi 7"), width);
+        assert_eq!(counter.width, width + 2);
+        assert_eq!(counter.pixels, canvas.into_render().pixels);
+    }
+
+    #[test]
+    fn translucent_renders_blend() {
+        let mut canvas = Canvas::new(2, 1, Color::WHITE);
+        canvas.draw_render((1, 0), &solid(5, 5, Color::rgba(0, 0, 0, 0x80)));
+        canvas.draw_render((0, 0), &solid(1, 1, Color::TRANSPARENT));
+        let render = canvas.into_render();
+        assert_eq!(Color::WHITE.to_bytes(), pixel_at(&render.pixels, 0));
+        assert_eq!([0x7F, 0x7F, 0x7F, 0xFF], pixel_at(&render.pixels, 1));
+    }
+
+    #[test]
+    fn stack_rows_and_columns() {
+        let renders = [solid(2, 4, RED), solid(3, 2, Color::WHITE)];
+
+        let row = stack(&renders, Direction::Row, 1, Color::BLACK);
+        assert_eq!((6, 4), (row.width, row.height));
+        let line = |render: &Render, y: usize| -> Vec<[u8; 4]> {
+            (0..render.width)
+                .map(|x| pixel_at(&render.pixels[y * render.width * 4..], x))
+                .collect()
+        };
+        let (r, w, b) = (
+            RED.to_bytes(),
+            Color::WHITE.to_bytes(),
+            Color::BLACK.to_bytes(),
+        );
+        // The shorter render is centered vertically.
+        assert_eq!(vec![r, r, b, b, b, b], line(&row, 0));
+        assert_eq!(vec![r, r, b, w, w, w], line(&row, 1));
+
+        let column = stack(&renders, Direction::Column, 1, Color::BLACK);
+        assert_eq!((3, 7), (column.width, column.height));
+        assert_eq!(vec![r, r, b], line(&column, 0));
+        assert_eq!(vec![b, b, b], line(&column, 4));
+        assert_eq!(vec![w, w, w], line(&column, 5));
+
+        assert_eq!((0, 0), {
+            let empty = stack(&[], Direction::Row, 1, Color::BLACK);
+            (empty.width, empty.height)
+        });
+    }
+
+    #[test]
+    fn parse_direction() {
+        assert_eq!(Direction::Row, "row".parse().unwrap());
+        assert_eq!(Direction::Column, "column".parse().unwrap());
+        assert!("diagonal".parse::<Direction>().is_err());
+    }
+}
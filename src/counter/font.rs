@@ -0,0 +1,2231 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bitmap fonts for rendering counters.
+//!
+//! Every font is a fixed-height bitmap font, and a handful of fonts are built in.
+//! BDF and PSF fonts can be loaded at runtime with [`LoadedFont`], and other fonts
+//! can be used by implementing the [`BitmapFont`] trait.
+
+use super::Color;
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    error::Error as StdError,
+    fmt,
+    hash::{Hash, Hasher},
+    mem::size_of,
+    path::Path,
+    sync::Arc,
+};
+
+mod bdf;
+mod psf;
+
+/// The bitmap for a single character.
+#[derive(Copy, Clone, Debug)]
+pub struct Glyph<'a> {
+    /// Width in pixels. The height is always the font's glyph height.
+    pub width: usize,
+    /// Pixels in row-major order, where any non-zero value is foreground.
+    pub bitmap: &'a [u8],
+}
+
+/// A fixed-height bitmap font.
+pub trait BitmapFont: Send + Sync {
+    /// Name of the font, which is used to select it.
+    fn name(&self) -> &str;
+
+    /// Size of a digit glyph in pixels as `(width, height)`.
+    fn glyph_size(&self) -> (usize, usize);
+
+    /// Horizontal spacing after each glyph in pixels.
+    fn kerning(&self) -> usize;
+
+    /// Look up the glyph for a character, if the font has one.
+    fn glyph(&self, c: char) -> Option<Glyph<'_>>;
+
+    /// Return the bitmap size for the given number of digits.
+    fn text_size(&self, char_count: usize) -> (usize, usize) {
+        let (width, height) = self.glyph_size();
+        (char_count * (width + self.kerning()), height)
+    }
+}
+
+impl fmt::Debug for dyn BitmapFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BitmapFont").field(&self.name()).finish()
+    }
+}
+
+/// Fonts are identified by name.
+impl PartialEq for dyn BitmapFont {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for dyn BitmapFont {}
+
+impl Hash for dyn BitmapFont {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name().hash(state);
+    }
+}
+
+/// Blit a single character into the given buffer in the given color.
+///
+/// Only the glyph's foreground pixels are written, so the background shows through.
+/// Characters that the font doesn't have a glyph for are skipped.
+pub(super) fn blit_char_into(
+    font: &dyn BitmapFont,
+    buffer: &mut [u8],
+    buffer_width: usize,
+    c: char,
+    color: Color,
+    x: usize,
+    y: usize,
+) {
+    if let Some(glyph) = font.glyph(c) {
+        blit_glyph_into(buffer, buffer_width, glyph, color, x, y);
+    }
+}
+
+fn blit_glyph_into(
+    buffer: &mut [u8],
+    buffer_width: usize,
+    glyph: Glyph<'_>,
+    color: Color,
+    x: usize,
+    y: usize,
+) {
+    if glyph.width == 0 {
+        return;
+    }
+    for (row, pixels) in glyph.bitmap.chunks_exact(glyph.width).enumerate() {
+        for (col, &pixel) in pixels.iter().enumerate() {
+            // Clip glyphs that are wider than the font's digits, which can happen with loaded fonts.
+            if pixel != 0 && x + col < buffer_width {
+                let dest_index = ((y + row) * buffer_width + (x + col)) * size_of::<u32>();
+                if let Some(dest) = buffer.get_mut(dest_index..dest_index + 4) {
+                    dest.copy_from_slice(&color.to_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// A font with glyphs that are compiled into the binary.
+///
+/// `N` is the number of pixels in each glyph.
+#[derive(Copy, Clone)]
+pub struct StaticFont<const N: usize> {
+    name: &'static str,
+    width: usize,
+    height: usize,
+    kerning: usize,
+    /// Characters that have glyphs, in the same order as `bitmaps`.
+    chars: &'static str,
+    bitmaps: &'static [[u8; N]],
+    /// Glyphs that are narrower than the digits, such as digit group separators.
+    narrow: &'static [NarrowGlyph],
+}
+
+/// A glyph in a [`StaticFont`] that is narrower than the font's digits.
+#[derive(Copy, Clone)]
+struct NarrowGlyph {
+    c: char,
+    width: usize,
+    bitmap: &'static [u8],
+}
+
+impl<const N: usize> BitmapFont for StaticFont<N> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn glyph_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn kerning(&self) -> usize {
+        self.kerning
+    }
+
+    fn glyph(&self, c: char) -> Option<Glyph<'_>> {
+        self.chars
+            .chars()
+            .position(|ch| ch == c)
+            .map(|index| Glyph {
+                width: self.width,
+                bitmap: &self.bitmaps[index],
+            })
+            .or_else(|| {
+                self.narrow
+                    .iter()
+                    .find(|glyph| glyph.c == c)
+                    .map(|glyph| Glyph {
+                        width: glyph.width,
+                        bitmap: glyph.bitmap,
+                    })
+            })
+    }
+}
+
+/// The original 8x16 font, which resembles old hit counters.
+pub static CLASSIC: StaticFont<{ 8 * 16 }> = StaticFont {
+    name: "classic",
+    width: 8,
+    height: 16,
+    kerning: 1,
+    chars: ASCII_CHARS,
+    bitmaps: &CLASSIC_GLYPHS,
+    narrow: &[
+        NarrowGlyph {
+            c: ',',
+            width: 2,
+            bitmap: &CLASSIC_COMMA,
+        },
+        NarrowGlyph {
+            c: '.',
+            width: 2,
+            bitmap: &CLASSIC_PERIOD,
+        },
+        NarrowGlyph {
+            c: '\'',
+            width: 2,
+            bitmap: &CLASSIC_APOSTROPHE,
+        },
+    ],
+};
+
+/// An 8x16 seven-segment LCD font, which only has digits, separators, spaces, minus signs,
+/// and the units of compact numbers.
+pub static LCD: StaticFont<{ 8 * 16 }> = StaticFont {
+    name: "lcd",
+    width: 8,
+    height: 16,
+    kerning: 2,
+    chars: "0123456789 -KMB",
+    bitmaps: &LCD_BITMAPS,
+    narrow: &[
+        NarrowGlyph {
+            c: ',',
+            width: 2,
+            bitmap: &CLASSIC_COMMA,
+        },
+        NarrowGlyph {
+            c: '.',
+            width: 2,
+            bitmap: &CLASSIC_PERIOD,
+        },
+        NarrowGlyph {
+            c: '\'',
+            width: 2,
+            bitmap: &CLASSIC_APOSTROPHE,
+        },
+    ],
+};
+
+/// A tiny 5x7 font.
+pub static TINY: StaticFont<{ 5 * 7 }> = StaticFont {
+    name: "tiny",
+    width: 5,
+    height: 7,
+    kerning: 1,
+    chars: ASCII_CHARS,
+    bitmaps: &TINY_GLYPHS,
+    narrow: &[
+        NarrowGlyph {
+            c: ',',
+            width: 2,
+            bitmap: &TINY_COMMA,
+        },
+        NarrowGlyph {
+            c: '.',
+            width: 1,
+            bitmap: &TINY_PERIOD,
+        },
+        NarrowGlyph {
+            c: '\'',
+            width: 1,
+            bitmap: &TINY_APOSTROPHE,
+        },
+    ],
+};
+
+/// A bold 14x20 font that looks like a 5x7 LED dot matrix.
+pub static LED: StaticFont<{ 14 * 20 }> = StaticFont {
+    name: "led",
+    width: 14,
+    height: 20,
+    kerning: 3,
+    chars: ASCII_CHARS,
+    bitmaps: &LED_GLYPHS,
+    narrow: &[
+        NarrowGlyph {
+            c: ',',
+            width: 5,
+            bitmap: &LED_COMMA,
+        },
+        NarrowGlyph {
+            c: '.',
+            width: 2,
+            bitmap: &LED_PERIOD,
+        },
+        NarrowGlyph {
+            c: '\'',
+            width: 2,
+            bitmap: &LED_APOSTROPHE,
+        },
+    ],
+};
+
+/// Names of the built-in fonts.
+pub const BUILTIN_FONT_NAMES: [&str; 4] = ["classic", "lcd", "tiny", "led"];
+
+/// Returns the built-in font with the given name.
+pub fn builtin(name: &str) -> Option<Arc<dyn BitmapFont>> {
+    BUILTIN_FONT_NAMES
+        .iter()
+        .position(|builtin| *builtin == name)
+        .map(|index| BUILTIN_FONTS[index].clone())
+}
+
+/// One shared instance of every built-in font, in the order of [`BUILTIN_FONT_NAMES`], so that
+/// styles using the same built-in font point to the same font.
+static BUILTIN_FONTS: Lazy<[Arc<dyn BitmapFont>; 4]> = Lazy::new(|| {
+    [
+        Arc::new(CLASSIC),
+        Arc::new(LCD),
+        Arc::new(TINY),
+        Arc::new(LED),
+    ]
+});
+
+/// An error loading a font from a file.
+#[derive(Debug)]
+pub enum FontError {
+    /// The font file couldn't be read.
+    Io(std::io::Error),
+    /// The font file isn't a BDF or PSF font.
+    UnknownFormat,
+    /// The BDF font is malformed.
+    InvalidBdf(String),
+    /// The PSF font is malformed.
+    InvalidPsf(String),
+    /// The font doesn't have glyphs for all the digits.
+    MissingDigits,
+}
+
+impl StdError for FontError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => f.write_str("failed to read font file"),
+            Self::UnknownFormat => f.write_str("font is not in BDF or PSF format"),
+            Self::InvalidBdf(msg) => write!(f, "invalid BDF font: {msg}"),
+            Self::InvalidPsf(msg) => write!(f, "invalid PSF font: {msg}"),
+            Self::MissingDigits => f.write_str("font is missing glyphs for digits"),
+        }
+    }
+}
+
+/// A single glyph in a [`LoadedFont`].
+struct LoadedGlyph {
+    width: usize,
+    bitmap: Vec<u8>,
+}
+
+/// A font loaded at runtime from a BDF or PSF (version 1 or 2) font file.
+///
+/// Every glyph is stored as a bitmap that is the full height of the font, and as wide as the
+/// glyph's advance. The glyph size is taken from the `0` glyph, and kerning is zero since
+/// these formats already include the spacing between glyphs in the glyphs themselves.
+pub struct LoadedFont {
+    name: String,
+    height: usize,
+    glyphs: HashMap<char, LoadedGlyph>,
+}
+
+impl LoadedFont {
+    /// Load a font file from disk, detecting whether it is a BDF or PSF font.
+    pub fn from_path(name: impl Into<String>, path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let bytes = std::fs::read(path).map_err(FontError::Io)?;
+        Self::from_bytes(name, &bytes)
+    }
+
+    /// Load a font from the contents of a BDF or PSF file.
+    pub fn from_bytes(name: impl Into<String>, bytes: &[u8]) -> Result<Self, FontError> {
+        let (height, glyphs) = if bytes.starts_with(b"STARTFONT") {
+            bdf::parse(bytes)?
+        } else if bytes.starts_with(&psf::PSF1_MAGIC) || bytes.starts_with(&psf::PSF2_MAGIC) {
+            psf::parse(bytes)?
+        } else {
+            return Err(FontError::UnknownFormat);
+        };
+        if !('0'..='9').all(|digit| glyphs.contains_key(&digit)) {
+            return Err(FontError::MissingDigits);
+        }
+        Ok(Self {
+            name: name.into(),
+            height,
+            glyphs,
+        })
+    }
+}
+
+impl BitmapFont for LoadedFont {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn glyph_size(&self) -> (usize, usize) {
+        (self.glyphs[&'0'].width, self.height)
+    }
+
+    fn kerning(&self) -> usize {
+        0
+    }
+
+    fn glyph(&self, c: char) -> Option<Glyph<'_>> {
+        self.glyphs.get(&c).map(|glyph| Glyph {
+            width: glyph.width,
+            bitmap: &glyph.bitmap,
+        })
+    }
+}
+
+/// Foreground
+const F: u8 = 1;
+
+/// Number of printable ASCII characters other than the digits and narrow separators.
+const TEXT_CHAR_COUNT: usize = 82;
+
+/// Characters that have full-width glyphs in the text-capable built-in fonts, which is all of
+/// printable ASCII except for the narrow separators. The first ten are the digits, and the rest
+/// are in the same order as [`TEXT_BITMAPS`].
+const ASCII_CHARS: &str = "0123456789 !\"#$%&()*+-/:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+static CLASSIC_GLYPHS: [[u8; 8 * 16]; 10 + TEXT_CHAR_COUNT] = with_text(
+    &CLASSIC_BITMAPS,
+    // Keep the classic font's 2px strokes, and align the text to the bottom of the digits.
+    &upscale_text(8, [0, 1, 3, 5, 6], [2, 4, 6, 8, 10, 12, 14]),
+);
+static TINY_GLYPHS: [[u8; 5 * 7]; 10 + TEXT_CHAR_COUNT] = with_text(&TINY_BITMAPS, &TEXT_BITMAPS);
+// Same as the digits, each dot becomes 2x2 pixels with a 1px gap between dots.
+static LED_GLYPHS: [[u8; 14 * 20]; 10 + TEXT_CHAR_COUNT] = with_text(
+    &LED_BITMAPS,
+    &upscale_text(14, [0, 3, 6, 9, 12], [0, 3, 6, 9, 12, 15, 18]),
+);
+
+/// Append the text glyphs to a font's digit glyphs, in the order of [`ASCII_CHARS`].
+const fn with_text<const N: usize>(
+    digits: &[[u8; N]; 10],
+    text: &[[u8; N]; TEXT_CHAR_COUNT],
+) -> [[u8; N]; 10 + TEXT_CHAR_COUNT] {
+    let mut glyphs = [[0; N]; 10 + TEXT_CHAR_COUNT];
+    let mut index = 0;
+    while index < glyphs.len() {
+        glyphs[index] = if index < 10 {
+            digits[index]
+        } else {
+            text[index - 10]
+        };
+        index += 1;
+    }
+    glyphs
+}
+
+/// Scale up [`TEXT_BITMAPS`] by drawing each pixel as a 2x2 block in a glyph that is `width`
+/// pixels wide. The blocks are placed at the given column and row offsets.
+const fn upscale_text<const N: usize>(
+    width: usize,
+    columns: [usize; 5],
+    rows: [usize; 7],
+) -> [[u8; N]; TEXT_CHAR_COUNT] {
+    let mut glyphs = [[0; N]; TEXT_CHAR_COUNT];
+    let mut index = 0;
+    while index < TEXT_CHAR_COUNT {
+        let mut pixel = 0;
+        while pixel < 5 * 7 {
+            if TEXT_BITMAPS[index][pixel] != 0 {
+                let (x, y) = (columns[pixel % 5], rows[pixel / 5]);
+                glyphs[index][y * width + x] = F;
+                glyphs[index][y * width + x + 1] = F;
+                glyphs[index][(y + 1) * width + x] = F;
+                glyphs[index][(y + 1) * width + x + 1] = F;
+            }
+            pixel += 1;
+        }
+        index += 1;
+    }
+    glyphs
+}
+
+#[rustfmt::skip]
+const CLASSIC_BITMAPS: [[u8; 8 * 16]; 10] = [
+    [ // 0
+        0,0,0,F,F,0,0,0,
+        0,0,F,F,F,F,0,0,
+        0,F,F,0,0,F,F,0,
+        0,F,0,0,0,0,F,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,F,0,F,F,
+        F,F,0,F,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,F,0,0,0,0,F,0,
+        0,F,F,0,0,F,F,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,F,F,0,0,0,
+    ],
+    [ // 1
+        0,0,0,F,F,0,0,0,
+        0,0,F,F,F,0,0,0,
+        0,F,F,F,F,0,0,0,
+        F,F,0,F,F,0,0,0,
+        F,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        F,F,F,F,F,F,F,F,
+        F,F,F,F,F,F,F,F,
+    ],
+    [ // 2
+        0,0,0,F,F,0,0,0,
+        0,F,F,F,F,F,0,0,
+        0,F,F,0,0,F,F,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,F,F,0,0,
+        0,0,0,0,F,F,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,F,F,0,0,0,0,
+        0,F,F,F,0,0,0,0,
+        F,F,F,F,F,F,F,F,
+        F,F,F,F,F,F,F,F,
+    ],
+    [ // 3
+        0,0,0,F,F,0,0,0,
+        0,F,F,F,F,F,F,0,
+        F,F,F,0,0,F,F,0,
+        F,F,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,F,F,0,
+        0,0,0,F,F,F,0,0,
+        0,0,0,F,F,F,0,0,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,F,0,0,F,F,0,
+        0,F,F,F,F,F,F,0,
+        0,0,0,F,F,0,0,0,
+    ],
+    [ // 4
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,F,F,F,0,
+        0,0,0,F,F,F,F,0,
+        0,0,0,F,0,F,F,0,
+        0,0,F,F,0,F,F,0,
+        0,F,F,0,0,F,F,0,
+        0,F,F,0,0,F,F,0,
+        F,F,0,0,0,F,F,0,
+        F,F,F,F,F,F,F,F,
+        F,F,F,F,F,F,F,F,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,F,F,F,F,
+        0,0,0,0,F,F,F,F,
+    ],
+    [ // 5
+        F,F,F,F,F,F,F,0,
+        F,F,F,F,F,F,F,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,F,F,F,0,0,
+        F,F,F,F,F,F,F,0,
+        0,F,0,0,0,F,F,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,F,0,0,F,F,0,
+        0,F,F,F,F,F,0,0,
+        0,0,0,F,F,0,0,0,
+    ],
+    [ // 6
+        0,0,0,F,F,0,0,0,
+        0,F,F,F,F,F,F,0,
+        0,F,F,0,0,F,F,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,F,F,0,0,0,
+        F,F,F,F,F,F,F,0,
+        F,F,F,0,0,F,F,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,F,0,0,0,0,F,0,
+        0,F,F,0,0,F,F,0,
+        0,F,F,F,F,F,F,0,
+        0,0,0,F,F,0,0,0,
+    ],
+    [ // 7
+        F,F,F,F,F,F,F,F,
+        F,F,F,F,F,F,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,0,F,F,0,
+        0,0,0,0,F,F,0,0,
+        0,0,0,0,F,F,0,0,
+        0,0,F,F,F,F,F,0,
+        0,0,F,F,F,F,F,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,
+    ],
+    [ // 8
+        0,0,0,F,F,0,0,0,
+        0,F,F,F,F,F,F,0,
+        0,F,F,0,0,F,F,0,
+        0,F,0,0,0,0,F,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,F,F,0,0,F,F,0,
+        0,F,F,F,F,F,F,0,
+        0,0,F,F,F,F,0,0,
+        0,F,F,0,0,F,F,0,
+        F,F,F,0,0,F,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,F,F,0,0,F,F,0,
+        0,F,F,F,F,F,F,0,
+        0,0,0,F,F,0,0,0,
+    ],
+    [ // 9
+        0,0,0,F,F,0,0,0,
+        0,F,F,F,F,F,F,0,
+        0,F,F,0,0,F,F,0,
+        0,F,0,0,0,0,F,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,F,F,0,0,F,F,F,
+        0,F,F,F,F,F,F,F,
+        0,0,0,F,F,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,F,F,0,0,F,F,0,
+        0,F,F,F,F,F,F,0,
+        0,0,0,F,F,0,0,0,
+    ],
+];
+
+#[rustfmt::skip]
+const LCD_BITMAPS: [[u8; 8 * 16]; 15] = [
+    [ // 0
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+    ],
+    [ // 1
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+    ],
+    [ // 2
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+    ],
+    [ // 3
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+    ],
+    [ // 4
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+    ],
+    [ // 5
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+    ],
+    [ // 6
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+    ],
+    [ // 7
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+    ],
+    [ // 8
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+    ],
+    [ // 9
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,F,F,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+    ],
+    [ // space
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+    ],
+    [ // -
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,F,F,F,F,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+    ],
+    [ // K
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,F,F,0,
+        F,F,0,0,F,F,0,0,
+        F,F,0,F,F,0,0,0,
+        F,F,0,F,0,0,0,0,
+        0,0,F,F,0,0,0,0,
+        0,0,F,F,0,0,0,0,
+        F,F,0,F,0,0,0,0,
+        F,F,0,F,F,0,0,0,
+        F,F,0,0,F,F,0,0,
+        F,F,0,0,0,F,F,0,
+        F,F,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+    ],
+    [ // M
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,F,0,0,F,F,F,
+        F,F,0,F,F,0,F,F,
+        F,F,0,F,F,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,
+    ],
+    [ // B
+        F,F,F,F,F,F,0,0,
+        F,F,F,F,F,F,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,F,F,F,F,0,0,
+        F,F,F,F,F,F,0,0,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,0,0,0,0,F,F,
+        F,F,F,F,F,F,0,0,
+        F,F,F,F,F,F,0,0,
+    ],
+];
+
+#[rustfmt::skip]
+const TINY_BITMAPS: [[u8; 5 * 7]; 10] = [
+    [ // 0
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // 1
+        0,0,F,0,0,
+        0,F,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,F,F,F,0,
+    ],
+    [ // 2
+        0,F,F,F,0,
+        F,0,0,0,F,
+        0,0,0,0,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        F,F,F,F,F,
+    ],
+    [ // 3
+        F,F,F,F,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+        0,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // 4
+        0,0,0,F,0,
+        0,0,F,F,0,
+        0,F,0,F,0,
+        F,0,0,F,0,
+        F,F,F,F,F,
+        0,0,0,F,0,
+        0,0,0,F,0,
+    ],
+    [ // 5
+        F,F,F,F,F,
+        F,0,0,0,0,
+        F,F,F,F,0,
+        0,0,0,0,F,
+        0,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // 6
+        0,0,F,F,0,
+        0,F,0,0,0,
+        F,0,0,0,0,
+        F,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // 7
+        F,F,F,F,F,
+        0,0,0,0,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+    ],
+    [ // 8
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // 9
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,F,
+        0,0,0,0,F,
+        0,0,0,F,0,
+        0,F,F,0,0,
+    ],
+];
+
+#[rustfmt::skip]
+const LED_BITMAPS: [[u8; 14 * 20]; 10] = [
+    [ // 0
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+    ],
+    [ // 1
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+    ],
+    [ // 2
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+    ],
+    [ // 3
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+    ],
+    [ // 4
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,F,F,0,0,0,
+        0,0,0,F,F,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,F,F,0,0,0,
+        F,F,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+    ],
+    [ // 5
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,F,F,0,F,F,0,F,F,0,0,0,
+        F,F,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+    ],
+    [ // 6
+        0,0,0,0,0,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,F,F,0,F,F,0,F,F,0,0,0,
+        F,F,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+    ],
+    [ // 7
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        F,F,0,F,F,0,F,F,0,F,F,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,F,F,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,0,0,0,0,0,0,0,0,
+    ],
+    [ // 8
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+    ],
+    [ // 9
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        F,F,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,F,F,0,F,F,
+        0,0,0,F,F,0,F,F,0,F,F,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,F,F,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,F,F,0,0,0,
+        0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,0,0,0,0,0,
+        0,0,0,F,F,0,F,F,0,0,0,0,0,0,
+    ],
+];
+
+#[rustfmt::skip]
+const CLASSIC_COMMA: [u8; 2 * 16] = [
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    F,F,
+    F,F,
+    0,F,
+    F,0,
+];
+
+#[rustfmt::skip]
+const CLASSIC_PERIOD: [u8; 2 * 16] = [
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    F,F,
+    F,F,
+];
+
+#[rustfmt::skip]
+const CLASSIC_APOSTROPHE: [u8; 2 * 16] = [
+    F,F,
+    F,F,
+    0,F,
+    F,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+];
+
+#[rustfmt::skip]
+const TINY_COMMA: [u8; 2 * 7] = [
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,F,
+    F,0,
+];
+
+#[rustfmt::skip]
+const TINY_PERIOD: [u8; 7] = [
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    F,
+];
+
+#[rustfmt::skip]
+const TINY_APOSTROPHE: [u8; 7] = [
+    F,
+    F,
+    0,
+    0,
+    0,
+    0,
+    0,
+];
+
+#[rustfmt::skip]
+const LED_COMMA: [u8; 5 * 20] = [
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,0,0,
+    0,0,0,F,F,
+    0,0,0,F,F,
+    0,0,0,0,0,
+    F,F,0,0,0,
+    F,F,0,0,0,
+];
+
+#[rustfmt::skip]
+const LED_PERIOD: [u8; 2 * 20] = [
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    F,F,
+    F,F,
+];
+
+#[rustfmt::skip]
+const LED_APOSTROPHE: [u8; 2 * 20] = [
+    F,F,
+    F,F,
+    0,0,
+    F,F,
+    F,F,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+    0,0,
+];
+
+#[rustfmt::skip]
+const TEXT_BITMAPS: [[u8; 5 * 7]; TEXT_CHAR_COUNT] = [
+    [ // space
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+    ],
+    [ // !
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,0,0,0,
+        0,0,F,0,0,
+    ],
+    [ // "
+        0,F,0,F,0,
+        0,F,0,F,0,
+        0,F,0,F,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+    ],
+    [ // #
+        0,F,0,F,0,
+        0,F,0,F,0,
+        F,F,F,F,F,
+        0,F,0,F,0,
+        F,F,F,F,F,
+        0,F,0,F,0,
+        0,F,0,F,0,
+    ],
+    [ // $
+        0,0,F,0,0,
+        0,F,F,F,F,
+        F,0,F,0,0,
+        0,F,F,F,0,
+        0,0,F,0,F,
+        F,F,F,F,0,
+        0,0,F,0,0,
+    ],
+    [ // %
+        F,F,0,0,0,
+        F,F,0,0,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        F,0,0,F,F,
+        0,0,0,F,F,
+    ],
+    [ // &
+        0,F,F,0,0,
+        F,0,0,F,0,
+        F,0,F,0,0,
+        0,F,0,0,0,
+        F,0,F,0,F,
+        F,0,0,F,0,
+        0,F,F,0,F,
+    ],
+    [ // (
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+    ],
+    [ // )
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+    ],
+    [ // *
+        0,0,0,0,0,
+        0,0,F,0,0,
+        F,0,F,0,F,
+        0,F,F,F,0,
+        F,0,F,0,F,
+        0,0,F,0,0,
+        0,0,0,0,0,
+    ],
+    [ // +
+        0,0,0,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        F,F,F,F,F,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,0,0,0,
+    ],
+    [ // -
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,F,F,F,F,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+    ],
+    [ // /
+        0,0,0,0,0,
+        0,0,0,0,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        F,0,0,0,0,
+        0,0,0,0,0,
+    ],
+    [ // :
+        0,0,0,0,0,
+        0,F,F,0,0,
+        0,F,F,0,0,
+        0,0,0,0,0,
+        0,F,F,0,0,
+        0,F,F,0,0,
+        0,0,0,0,0,
+    ],
+    [ // ;
+        0,0,0,0,0,
+        0,F,F,0,0,
+        0,F,F,0,0,
+        0,0,0,0,0,
+        0,F,F,0,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+    ],
+    [ // <
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        F,0,0,0,0,
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+    ],
+    [ // =
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,F,F,F,F,
+        0,0,0,0,0,
+        F,F,F,F,F,
+        0,0,0,0,0,
+        0,0,0,0,0,
+    ],
+    [ // >
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+        0,0,0,0,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+    ],
+    [ // ?
+        0,F,F,F,0,
+        F,0,0,0,F,
+        0,0,0,0,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,0,0,0,0,
+        0,0,F,0,0,
+    ],
+    [ // @
+        0,F,F,F,0,
+        F,0,0,0,F,
+        0,0,0,0,F,
+        0,F,F,0,F,
+        F,0,F,0,F,
+        F,0,F,0,F,
+        0,F,F,F,0,
+    ],
+    [ // A
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,F,F,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // B
+        F,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,F,F,0,
+    ],
+    [ // C
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // D
+        F,F,F,0,0,
+        F,0,0,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,F,0,
+        F,F,F,0,0,
+    ],
+    [ // E
+        F,F,F,F,F,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,F,F,F,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,F,F,F,F,
+    ],
+    [ // F
+        F,F,F,F,F,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,F,F,F,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+    ],
+    [ // G
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,0,
+        F,0,F,F,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,F,
+    ],
+    [ // H
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,F,F,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // I
+        0,F,F,F,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,F,F,F,0,
+    ],
+    [ // J
+        0,0,F,F,F,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        F,0,0,F,0,
+        0,F,F,0,0,
+    ],
+    [ // K
+        F,0,0,0,F,
+        F,0,0,F,0,
+        F,0,F,0,0,
+        F,F,0,0,0,
+        F,0,F,0,0,
+        F,0,0,F,0,
+        F,0,0,0,F,
+    ],
+    [ // L
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,F,F,F,F,
+    ],
+    [ // M
+        F,0,0,0,F,
+        F,F,0,F,F,
+        F,0,F,0,F,
+        F,0,F,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // N
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,0,0,F,
+        F,0,F,0,F,
+        F,0,0,F,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // O
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // P
+        F,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,F,F,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+    ],
+    [ // Q
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,F,0,F,
+        F,0,0,F,0,
+        0,F,F,0,F,
+    ],
+    [ // R
+        F,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,F,F,0,
+        F,0,F,0,0,
+        F,0,0,F,0,
+        F,0,0,0,F,
+    ],
+    [ // S
+        0,F,F,F,F,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        0,F,F,F,0,
+        0,0,0,0,F,
+        0,0,0,0,F,
+        F,F,F,F,0,
+    ],
+    [ // T
+        F,F,F,F,F,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+    ],
+    [ // U
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // V
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,0,F,0,
+        0,0,F,0,0,
+    ],
+    [ // W
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,F,0,F,
+        F,0,F,0,F,
+        F,0,F,0,F,
+        0,F,0,F,0,
+    ],
+    [ // X
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,0,F,0,
+        0,0,F,0,0,
+        0,F,0,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // Y
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,0,F,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+    ],
+    [ // Z
+        F,F,F,F,F,
+        0,0,0,0,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        F,0,0,0,0,
+        F,F,F,F,F,
+    ],
+    [ // [
+        0,F,F,F,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,F,F,0,
+    ],
+    [ // \\
+        0,0,0,0,0,
+        F,0,0,0,0,
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+        0,0,0,0,F,
+        0,0,0,0,0,
+    ],
+    [ // ]
+        0,F,F,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        0,F,F,F,0,
+    ],
+    [ // ^
+        0,0,F,0,0,
+        0,F,0,F,0,
+        F,0,0,0,F,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+    ],
+    [ // _
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,F,F,F,F,
+    ],
+    [ // `
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+    ],
+    [ // a
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,F,F,F,0,
+        0,0,0,0,F,
+        0,F,F,F,F,
+        F,0,0,0,F,
+        0,F,F,F,F,
+    ],
+    [ // b
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,F,F,0,
+        F,F,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,F,F,F,0,
+    ],
+    [ // c
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,F,F,F,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // d
+        0,0,0,0,F,
+        0,0,0,0,F,
+        0,F,F,0,F,
+        F,0,0,F,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,F,
+    ],
+    [ // e
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,F,F,F,F,
+        F,0,0,0,0,
+        0,F,F,F,0,
+    ],
+    [ // f
+        0,0,F,F,0,
+        0,F,0,0,F,
+        0,F,0,0,0,
+        F,F,F,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+    ],
+    [ // g
+        0,0,0,0,0,
+        0,F,F,F,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,F,
+        0,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // h
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,F,F,0,
+        F,F,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // i
+        0,0,F,0,0,
+        0,0,0,0,0,
+        0,F,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,F,F,F,0,
+    ],
+    [ // j
+        0,0,0,F,0,
+        0,0,0,0,0,
+        0,0,F,F,0,
+        0,0,0,F,0,
+        0,0,0,F,0,
+        F,0,0,F,0,
+        0,F,F,0,0,
+    ],
+    [ // k
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,F,0,
+        F,0,F,0,0,
+        F,F,0,0,0,
+        F,0,F,0,0,
+        F,0,0,F,0,
+    ],
+    [ // l
+        0,F,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,F,F,F,0,
+    ],
+    [ // m
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,F,0,F,0,
+        F,0,F,0,F,
+        F,0,F,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // n
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,0,F,F,0,
+        F,F,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+    ],
+    [ // o
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,F,F,F,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // p
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,F,F,F,0,
+        F,0,0,0,F,
+        F,F,F,F,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+    ],
+    [ // q
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,F,F,0,F,
+        F,0,0,F,F,
+        0,F,F,F,F,
+        0,0,0,0,F,
+        0,0,0,0,F,
+    ],
+    [ // r
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,0,F,F,0,
+        F,F,0,0,F,
+        F,0,0,0,0,
+        F,0,0,0,0,
+        F,0,0,0,0,
+    ],
+    [ // s
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,F,F,F,0,
+        F,0,0,0,0,
+        0,F,F,F,0,
+        0,0,0,0,F,
+        F,F,F,F,0,
+    ],
+    [ // t
+        0,F,0,0,0,
+        0,F,0,0,0,
+        F,F,F,0,0,
+        0,F,0,0,0,
+        0,F,0,0,0,
+        0,F,0,0,F,
+        0,0,F,F,0,
+    ],
+    [ // u
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,F,F,
+        0,F,F,0,F,
+    ],
+    [ // v
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,0,F,0,
+        0,0,F,0,0,
+    ],
+    [ // w
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        F,0,F,0,F,
+        F,0,F,0,F,
+        0,F,0,F,0,
+    ],
+    [ // x
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,0,0,0,F,
+        0,F,0,F,0,
+        0,0,F,0,0,
+        0,F,0,F,0,
+        F,0,0,0,F,
+    ],
+    [ // y
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,0,0,0,F,
+        F,0,0,0,F,
+        0,F,F,F,F,
+        0,0,0,0,F,
+        0,F,F,F,0,
+    ],
+    [ // z
+        0,0,0,0,0,
+        0,0,0,0,0,
+        F,F,F,F,F,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        F,F,F,F,F,
+    ],
+    [ // {
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+    ],
+    [ // |
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+    ],
+    [ // }
+        0,F,0,0,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,0,0,F,0,
+        0,0,F,0,0,
+        0,0,F,0,0,
+        0,F,0,0,0,
+    ],
+    [ // ~
+        0,0,0,0,0,
+        0,0,0,0,0,
+        0,F,0,0,0,
+        F,0,F,0,F,
+        0,0,0,F,0,
+        0,0,0,0,0,
+        0,0,0,0,0,
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_fonts_have_every_digit_separator_and_unit() {
+        for name in BUILTIN_FONT_NAMES {
+            let font = builtin(name).unwrap();
+            assert_eq!(name, font.name());
+            let (width, height) = font.glyph_size();
+            // Compact number units are drawn in digit cells, so they need to be as wide as digits.
+            for digit in ('0'..='9').chain(['K', 'M', 'B']) {
+                let glyph = font.glyph(digit).unwrap();
+                assert_eq!(width, glyph.width, "{name} {digit}");
+                assert_eq!(width * height, glyph.bitmap.len(), "{name} {digit}");
+            }
+            for separator in [',', '.', '\''] {
+                let glyph = font.glyph(separator).unwrap();
+                assert!(glyph.width < width, "{name} {separator}");
+                assert_eq!(
+                    glyph.width * height,
+                    glyph.bitmap.len(),
+                    "{name} {separator}"
+                );
+            }
+        }
+        assert!(builtin("comic-sans").is_none());
+    }
+
+    #[test]
+    fn text_fonts_have_printable_ascii() {
+        assert_eq!(10 + TEXT_CHAR_COUNT, ASCII_CHARS.chars().count());
+        for name in ["classic", "tiny", "led"] {
+            let font = builtin(name).unwrap();
+            for c in ' '..='~' {
+                assert!(font.glyph(c).is_some(), "{name} {c}");
+            }
+        }
+        assert!(LCD.glyph('-').is_some());
+        assert!(LCD.glyph('A').is_none());
+    }
+
+    #[test]
+    fn fonts_compare_by_name() {
+        let classic: Arc<dyn BitmapFont> = Arc::new(CLASSIC);
+        assert_eq!(&*classic, &*builtin("classic").unwrap());
+        assert_ne!(&*classic, &*builtin("lcd").unwrap());
+    }
+
+    #[test]
+    fn builtin_fonts_are_shared() {
+        for name in BUILTIN_FONT_NAMES {
+            assert_eq!(name, builtin(name).unwrap().name());
+            assert!(Arc::ptr_eq(
+                &builtin(name).unwrap(),
+                &builtin(name).unwrap()
+            ));
+        }
+        assert!(builtin("missing").is_none());
+    }
+}
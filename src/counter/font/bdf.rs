@@ -0,0 +1,314 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing for BDF (Glyph Bitmap Distribution Format) fonts.
+
+use super::{FontError, LoadedGlyph, F};
+use std::collections::HashMap;
+
+/// A BDF bounding box, as `(width, height, x offset, y offset)`.
+type BoundingBox = (i32, i32, i32, i32);
+
+/// Largest width, height, offset, or advance that a glyph can have, which is far larger
+/// than any bitmap font, but keeps a corrupt font from allocating huge glyphs.
+const MAX_GLYPH_SIZE: i32 = 1024;
+
+/// Parse a BDF font into its glyph height and glyphs.
+///
+/// Glyphs are positioned in a cell that is the height of the font's bounding box
+/// using their baseline offsets, and the cell is as wide as the glyph's advance.
+pub(super) fn parse(bytes: &[u8]) -> Result<(usize, HashMap<char, LoadedGlyph>), FontError> {
+    // BDF files are ASCII apart from comments and properties, which can be in any encoding.
+    let text = String::from_utf8_lossy(bytes);
+    let mut lines = text.lines().map(str::trim);
+
+    let mut font_box: Option<BoundingBox> = None;
+    let mut glyphs = HashMap::new();
+    while let Some(line) = lines.next() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("FONTBOUNDINGBOX") => {
+                let bounding_box = parse_bounding_box(words)?;
+                if bounding_box.1 <= 0 {
+                    return Err(invalid("FONTBOUNDINGBOX has no height"));
+                }
+                font_box = Some(bounding_box);
+            }
+            Some("STARTCHAR") => {
+                let font_box =
+                    font_box.ok_or_else(|| invalid("STARTCHAR before FONTBOUNDINGBOX"))?;
+                if let Some((c, glyph)) = parse_char(&mut lines, font_box)? {
+                    glyphs.insert(c, glyph);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (_, height, _, _) = font_box.ok_or_else(|| invalid("missing FONTBOUNDINGBOX"))?;
+    Ok((height as usize, glyphs))
+}
+
+/// Parse a single character, up to and including its `ENDCHAR` line.
+///
+/// Returns `None` for characters that don't have a Unicode encoding.
+fn parse_char<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    font_box: BoundingBox,
+) -> Result<Option<(char, LoadedGlyph)>, FontError> {
+    let (font_width, font_height, _, font_y) = font_box;
+    let mut c = None;
+    let mut advance = font_width;
+    let mut glyph_box = font_box;
+    loop {
+        let line = lines.next().ok_or_else(|| invalid("missing ENDCHAR"))?;
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("ENCODING") => {
+                c = parse_number(words.next())
+                    .ok()
+                    .and_then(|code| u32::try_from(code).ok())
+                    .and_then(char::from_u32);
+            }
+            Some("DWIDTH") => {
+                advance = parse_number(words.next())?;
+                if advance > MAX_GLYPH_SIZE {
+                    return Err(invalid("DWIDTH is too large"));
+                }
+            }
+            Some("BBX") => glyph_box = parse_bounding_box(words)?,
+            Some("BITMAP") => break,
+            Some("ENDCHAR") => return Err(invalid("character is missing its BITMAP")),
+            _ => {}
+        }
+    }
+
+    // Place the glyph's bitmap in a cell using the distance from the top of the font's
+    // bounding box to the top of the glyph's bounding box.
+    let (box_width, box_height, box_x, box_y) = glyph_box;
+    let width = advance.max(0) as usize;
+    let height = font_height as usize;
+    let top = font_height
+        .checked_add(font_y)
+        .zip(box_height.checked_add(box_y))
+        .and_then(|(font_top, box_top)| font_top.checked_sub(box_top))
+        .ok_or_else(|| invalid("BBX offset is out of range"))?;
+    let mut bitmap = vec![0; width * height];
+    for row in 0..box_height {
+        let line = lines
+            .next()
+            .ok_or_else(|| invalid("BITMAP is missing rows"))?;
+        let bytes = parse_hex_row(line)?;
+        for col in 0..box_width {
+            let byte = bytes.get(col as usize / 8).copied().unwrap_or(0);
+            let (x, y) = (box_x + col, top + row);
+            let set = byte & (0x80 >> (col % 8)) != 0;
+            if set && (0..width as i32).contains(&x) && (0..height as i32).contains(&y) {
+                bitmap[y as usize * width + x as usize] = F;
+            }
+        }
+    }
+    if lines.next() != Some("ENDCHAR") {
+        return Err(invalid("BITMAP has extra rows"));
+    }
+
+    Ok(c.map(|c| (c, LoadedGlyph { width, bitmap })))
+}
+
+/// Parse a bounding box, which needs a size from zero to [`MAX_GLYPH_SIZE`], and offsets
+/// no further than that in either direction.
+fn parse_bounding_box<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+) -> Result<BoundingBox, FontError> {
+    let bounding_box = (
+        parse_number(words.next())?,
+        parse_number(words.next())?,
+        parse_number(words.next())?,
+        parse_number(words.next())?,
+    );
+    let (width, height, x, y) = bounding_box;
+    let size = 0..=MAX_GLYPH_SIZE;
+    let offset = -MAX_GLYPH_SIZE..=MAX_GLYPH_SIZE;
+    if !size.contains(&width)
+        || !size.contains(&height)
+        || !offset.contains(&x)
+        || !offset.contains(&y)
+    {
+        return Err(invalid("bounding box is out of range"));
+    }
+    Ok(bounding_box)
+}
+
+fn parse_number(word: Option<&str>) -> Result<i32, FontError> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| invalid("expected a number"))
+}
+
+fn parse_hex_row(line: &str) -> Result<Vec<u8>, FontError> {
+    if line.len() % 2 == 1 || !line.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid("BITMAP rows must be hex bytes"));
+    }
+    Ok((0..line.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&line[index..index + 2], 16).unwrap())
+        .collect())
+}
+
+fn invalid(msg: &str) -> FontError {
+    FontError::InvalidBdf(msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: &str = "\
+STARTFONT 2.1
+FONT -test-fixed-medium-r-normal--4-40-75-75-c-40-iso10646-1
+SIZE 4 75 75
+FONTBOUNDINGBOX 4 5 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 4
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 2
+STARTCHAR one
+ENCODING 49
+SWIDTH 1000 0
+DWIDTH 4 0
+BBX 2 4 1 0
+BITMAP
+40
+C0
+40
+40
+ENDCHAR
+STARTCHAR comma
+ENCODING 44
+DWIDTH 2 0
+BBX 1 2 0 -1
+BITMAP
+80
+80
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+DWIDTH 4 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn parse_glyphs() {
+        let (height, glyphs) = parse(FONT.as_bytes()).unwrap();
+        assert_eq!(5, height);
+        assert_eq!(2, glyphs.len(), "unencoded glyphs are skipped");
+
+        let one = &glyphs[&'1'];
+        assert_eq!(4, one.width);
+        #[rustfmt::skip]
+        assert_eq!(
+            vec![
+                0,0,F,0,
+                0,F,F,0,
+                0,0,F,0,
+                0,0,F,0,
+                0,0,0,0,
+            ],
+            one.bitmap
+        );
+
+        let comma = &glyphs[&','];
+        assert_eq!(2, comma.width);
+        #[rustfmt::skip]
+        assert_eq!(
+            vec![
+                0,0,
+                0,0,
+                0,0,
+                F,0,
+                F,0,
+            ],
+            comma.bitmap
+        );
+    }
+
+    #[test]
+    fn missing_bitmap_rows() {
+        let font = FONT.replace("C0\n", "");
+        assert!(matches!(
+            parse(font.as_bytes()),
+            Err(FontError::InvalidBdf(_))
+        ));
+    }
+
+    #[test]
+    fn negative_height() {
+        let font = FONT.replace("FONTBOUNDINGBOX 4 5 0 -1", "FONTBOUNDINGBOX 8 -1 0 0");
+        assert!(matches!(
+            parse(font.as_bytes()),
+            Err(FontError::InvalidBdf(_))
+        ));
+    }
+
+    #[test]
+    fn oversize_boxes() {
+        for (from, to) in [
+            (
+                "FONTBOUNDINGBOX 4 5 0 -1",
+                "FONTBOUNDINGBOX 4 2000000000 0 -1",
+            ),
+            ("BBX 2 4 1 0", "BBX 2000000000 4 1 0"),
+            ("DWIDTH 4 0", "DWIDTH 2000000000 0"),
+        ] {
+            let font = FONT.replace(from, to);
+            assert!(
+                matches!(parse(font.as_bytes()), Err(FontError::InvalidBdf(_))),
+                "{to}"
+            );
+        }
+    }
+
+    #[test]
+    fn overflowing_offsets() {
+        for (from, to) in [
+            (
+                "FONTBOUNDINGBOX 4 5 0 -1",
+                "FONTBOUNDINGBOX 4 5 0 2147483647",
+            ),
+            ("BBX 2 4 1 0", "BBX 2 4 1 -2147483648"),
+        ] {
+            let font = FONT.replace(from, to);
+            assert!(
+                matches!(parse(font.as_bytes()), Err(FontError::InvalidBdf(_))),
+                "{to}"
+            );
+        }
+    }
+
+    #[test]
+    fn missing_bounding_box() {
+        let font = FONT.replace("FONTBOUNDINGBOX 4 5 0 -1\n", "");
+        assert!(matches!(
+            parse(font.as_bytes()),
+            Err(FontError::InvalidBdf(_))
+        ));
+    }
+}
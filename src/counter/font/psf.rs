@@ -0,0 +1,301 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing for PC Screen Font (PSF) fonts, which are commonly used as Linux console fonts.
+
+use super::{FontError, LoadedGlyph, F};
+use std::collections::HashMap;
+
+pub(super) const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+pub(super) const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+
+/// PSF1 mode bit for fonts with 512 glyphs instead of 256.
+const PSF1_MODE_512: u8 = 0x01;
+/// PSF1 mode bits for fonts with a Unicode table.
+const PSF1_MODE_HAS_TABLE: u8 = 0x02 | 0x04;
+/// PSF1 Unicode table value that separates glyphs.
+const PSF1_SEPARATOR: u16 = 0xFFFF;
+/// PSF1 Unicode table value that starts a sequence of code points.
+const PSF1_START_SEQUENCE: u16 = 0xFFFE;
+
+/// PSF2 flag for fonts with a Unicode table.
+const PSF2_FLAG_HAS_TABLE: u32 = 0x01;
+/// PSF2 Unicode table byte that separates glyphs.
+const PSF2_SEPARATOR: u8 = 0xFF;
+/// PSF2 Unicode table byte that starts a sequence of code points.
+const PSF2_START_SEQUENCE: u8 = 0xFE;
+
+/// The parts of a PSF header that are common between versions 1 and 2.
+struct Header {
+    glyph_count: usize,
+    bytes_per_glyph: usize,
+    width: usize,
+    height: usize,
+    /// Offset to the first glyph.
+    glyphs_offset: usize,
+    has_table: bool,
+    version: u8,
+}
+
+/// Parse a PSF font into its glyph height and glyphs.
+///
+/// Glyphs are mapped to characters with the font's Unicode table if it has one.
+/// Otherwise, glyphs are assumed to be in code point order.
+pub(super) fn parse(bytes: &[u8]) -> Result<(usize, HashMap<char, LoadedGlyph>), FontError> {
+    let header = parse_header(bytes)?;
+    let row_bytes = header.width / 8 + usize::from(header.width % 8 > 0);
+    if header.width == 0 || header.height == 0 || header.bytes_per_glyph < row_bytes * header.height
+    {
+        return Err(invalid("glyph size doesn't match its dimensions"));
+    }
+
+    let glyphs_end = header
+        .glyph_count
+        .checked_mul(header.bytes_per_glyph)
+        .and_then(|size| size.checked_add(header.glyphs_offset))
+        .ok_or_else(|| invalid("glyph count is too large"))?;
+    let glyph_data = bytes
+        .get(header.glyphs_offset..glyphs_end)
+        .ok_or_else(|| invalid("file is too short for its glyphs"))?;
+    let bitmaps: Vec<Vec<u8>> = glyph_data
+        .chunks_exact(header.bytes_per_glyph)
+        .map(|data| {
+            let mut bitmap = vec![0; header.width * header.height];
+            for (row, row_data) in data.chunks_exact(row_bytes).take(header.height).enumerate() {
+                for col in 0..header.width {
+                    if row_data[col / 8] & (0x80 >> (col % 8)) != 0 {
+                        bitmap[row * header.width + col] = F;
+                    }
+                }
+            }
+            bitmap
+        })
+        .collect();
+
+    let mapping = if header.has_table {
+        let table = &bytes[glyphs_end..];
+        match header.version {
+            1 => parse_psf1_table(table),
+            _ => parse_psf2_table(table),
+        }
+    } else {
+        (0..header.glyph_count)
+            .filter_map(|index| char::from_u32(index as u32).map(|c| (c, index)))
+            .collect()
+    };
+
+    let glyphs = mapping
+        .into_iter()
+        .filter(|&(_, index)| index < bitmaps.len())
+        .map(|(c, index)| {
+            let glyph = LoadedGlyph {
+                width: header.width,
+                bitmap: bitmaps[index].clone(),
+            };
+            (c, glyph)
+        })
+        .collect();
+    Ok((header.height, glyphs))
+}
+
+fn parse_header(bytes: &[u8]) -> Result<Header, FontError> {
+    if bytes.starts_with(&PSF1_MAGIC) {
+        let (mode, height) = match bytes {
+            [_, _, mode, height, ..] => (*mode, *height as usize),
+            _ => return Err(invalid("file is too short for its header")),
+        };
+        Ok(Header {
+            glyph_count: if mode & PSF1_MODE_512 != 0 { 512 } else { 256 },
+            bytes_per_glyph: height,
+            width: 8,
+            height,
+            glyphs_offset: 4,
+            has_table: mode & PSF1_MODE_HAS_TABLE != 0,
+            version: 1,
+        })
+    } else if bytes.starts_with(&PSF2_MAGIC) {
+        let field = |index: usize| {
+            bytes
+                .get(index * 4..index * 4 + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+                .ok_or_else(|| invalid("file is too short for its header"))
+        };
+        Ok(Header {
+            glyphs_offset: field(2)?,
+            has_table: field(3)? as u32 & PSF2_FLAG_HAS_TABLE != 0,
+            glyph_count: field(4)?,
+            bytes_per_glyph: field(5)?,
+            height: field(6)?,
+            width: field(7)?,
+            version: 2,
+        })
+    } else {
+        Err(FontError::UnknownFormat)
+    }
+}
+
+/// Parse a PSF1 Unicode table of little-endian UCS-2 values into `(character, glyph index)` pairs.
+fn parse_psf1_table(table: &[u8]) -> Vec<(char, usize)> {
+    let mut mapping = Vec::new();
+    let mut index = 0;
+    let mut in_sequence = false;
+    for value in table
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    {
+        match value {
+            PSF1_SEPARATOR => {
+                index += 1;
+                in_sequence = false;
+            }
+            PSF1_START_SEQUENCE => in_sequence = true,
+            _ if !in_sequence => {
+                if let Some(c) = char::from_u32(value as u32) {
+                    mapping.push((c, index));
+                }
+            }
+            _ => {}
+        }
+    }
+    mapping
+}
+
+/// Parse a PSF2 Unicode table of UTF-8 strings into `(character, glyph index)` pairs.
+fn parse_psf2_table(table: &[u8]) -> Vec<(char, usize)> {
+    table
+        .split(|&b| b == PSF2_SEPARATOR)
+        .enumerate()
+        .flat_map(|(index, entry)| {
+            // Only single code points are supported, so skip the sequences.
+            let singles = entry
+                .split(|&b| b == PSF2_START_SEQUENCE)
+                .next()
+                .unwrap_or_default();
+            String::from_utf8_lossy(singles)
+                .chars()
+                .filter(|&c| c != char::REPLACEMENT_CHARACTER)
+                .map(move |c| (c, index))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn invalid(msg: &str) -> FontError {
+    FontError::InvalidPsf(msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An 8x2 glyph with the left half set on the first row, and the right half on the second.
+    const GLYPH: [u8; 2] = [0xF0, 0x0F];
+
+    fn psf1(mode: u8, glyph_count: usize, table: &[u16]) -> Vec<u8> {
+        let mut font = vec![PSF1_MAGIC[0], PSF1_MAGIC[1], mode, 2];
+        for _ in 0..glyph_count {
+            font.extend_from_slice(&GLYPH);
+        }
+        for value in table {
+            font.extend_from_slice(&value.to_le_bytes());
+        }
+        font
+    }
+
+    fn psf2(glyph_count: usize, table: Option<&[u8]>) -> Vec<u8> {
+        let mut font = PSF2_MAGIC.to_vec();
+        let flags = table.map(|_| PSF2_FLAG_HAS_TABLE).unwrap_or(0);
+        // version, header size, flags, length, glyph size, height, width
+        for field in [0, 32, flags, glyph_count as u32, 4, 2, 12] {
+            font.extend_from_slice(&field.to_le_bytes());
+        }
+        for _ in 0..glyph_count {
+            // 12 pixels wide needs two bytes per row.
+            font.extend_from_slice(&[0xFF, 0xF0, 0x00, 0x10]);
+        }
+        font.extend_from_slice(table.unwrap_or_default());
+        font
+    }
+
+    #[test]
+    fn psf1_without_table() {
+        let (height, glyphs) = parse(&psf1(0, 256, &[])).unwrap();
+        assert_eq!(2, height);
+        assert_eq!(256, glyphs.len());
+
+        let glyph = &glyphs[&'7'];
+        assert_eq!(8, glyph.width);
+        #[rustfmt::skip]
+        assert_eq!(
+            vec![
+                F,F,F,F,0,0,0,0,
+                0,0,0,0,F,F,F,F,
+            ],
+            glyph.bitmap
+        );
+    }
+
+    #[test]
+    fn psf1_with_table() {
+        let table = [
+            '0' as u16,
+            'O' as u16,
+            PSF1_SEPARATOR,
+            PSF1_START_SEQUENCE,
+            'x' as u16,
+            PSF1_SEPARATOR,
+            '1' as u16,
+            PSF1_SEPARATOR,
+        ];
+        let (_, glyphs) = parse(&psf1(PSF1_MODE_512 | 0x02, 512, &table)).unwrap();
+        let mut chars: Vec<_> = glyphs.keys().copied().collect();
+        chars.sort();
+        assert_eq!(vec!['0', '1', 'O'], chars);
+    }
+
+    #[test]
+    fn psf2_with_table() {
+        let mut table = b"0".to_vec();
+        table.push(PSF2_SEPARATOR);
+        table.extend_from_slice("1\u{00E9}".as_bytes());
+        table.push(PSF2_START_SEQUENCE);
+        table.extend_from_slice(b"xy");
+        table.push(PSF2_SEPARATOR);
+        let (height, glyphs) = parse(&psf2(2, Some(&table))).unwrap();
+        assert_eq!(2, height);
+        assert_eq!(3, glyphs.len());
+        assert!(glyphs.contains_key(&'\u{00E9}'));
+
+        let glyph = &glyphs[&'1'];
+        assert_eq!(12, glyph.width);
+        #[rustfmt::skip]
+        assert_eq!(
+            vec![
+                F,F,F,F,F,F,F,F,F,F,F,F,
+                0,0,0,0,0,0,0,0,0,0,0,F,
+            ],
+            glyph.bitmap
+        );
+    }
+
+    #[test]
+    fn truncated_glyphs() {
+        let mut font = psf2(2, None);
+        font.truncate(font.len() - 1);
+        assert!(matches!(parse(&font), Err(FontError::InvalidPsf(_))));
+    }
+}
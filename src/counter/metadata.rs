@@ -0,0 +1,186 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Text and color space metadata for PNG images.
+
+use std::{
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Information to embed in a PNG image so that it describes itself, such as when it's archived.
+///
+/// Text is written to `tEXt` chunks, or to `iTXt` chunks if it isn't Latin-1.
+/// The default metadata writes nothing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PngMetadata {
+    /// Name of the counter, which is written as the `Title`.
+    pub name: Option<String>,
+    /// The count shown in the image, which is written as the `Count`.
+    pub count: Option<usize>,
+    /// When the image was generated, which is written as the `Creation Time` in RFC 3339 format.
+    pub created: Option<SystemTime>,
+    /// Whether to add an `sRGB` chunk, which tells viewers that the colors are sRGB
+    /// rather than leaving them to guess.
+    pub srgb: bool,
+}
+
+impl PngMetadata {
+    /// Add the metadata to the chunks that an encoder writes.
+    pub(super) fn apply<W: io::Write>(
+        &self,
+        encoder: &mut png::Encoder<'_, W>,
+    ) -> Result<(), png::EncodingError> {
+        let mut text = Vec::new();
+        if let Some(name) = &self.name {
+            text.push(("Title", name.clone()));
+        }
+        if let Some(count) = self.count {
+            text.push(("Count", count.to_string()));
+        }
+        if let Some(created) = self.created {
+            text.push(("Creation Time", rfc3339(created)));
+        }
+        for (keyword, value) in text {
+            if value.chars().all(|c| u32::from(c) <= 0xFF) {
+                encoder.add_text_chunk(keyword.into(), value)?;
+            } else {
+                encoder.add_itxt_chunk(keyword.into(), value)?;
+            }
+        }
+        if self.srgb {
+            encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+        }
+        Ok(())
+    }
+}
+
+/// Format a time as an RFC 3339 timestamp in UTC, such as `2023-11-14T22:13:20Z`.
+fn rfc3339(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // Convert days since the epoch to a Gregorian date with Howard Hinnant's `civil_from_days`
+    // algorithm, which counts 400-year eras of 146,097 days starting on March 1st, 0000.
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months start from March, so that the leap day is at the end of the year.
+    let march_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * march_month + 2) / 5 + 1;
+    let month = if march_month < 10 {
+        march_month + 3
+    } else {
+        march_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::Render;
+    use std::time::Duration;
+
+    fn render() -> Render {
+        Render {
+            width: 1,
+            height: 1,
+            pixels: vec![0xFF; 4],
+        }
+    }
+
+    fn decode_info(png: &[u8]) -> png::Info<'static> {
+        let reader = png::Decoder::new(png).read_info().unwrap();
+        reader.info().clone()
+    }
+
+    #[test]
+    fn png_describes_itself() {
+        let metadata = PngMetadata {
+            name: Some("garden".into()),
+            count: Some(1234),
+            created: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            srgb: true,
+        };
+        let png = render().to_png_bytes_with_metadata(&metadata).unwrap();
+        let info = decode_info(&png);
+        let text: Vec<(&str, &str)> = info
+            .uncompressed_latin1_text
+            .iter()
+            .map(|chunk| (chunk.keyword.as_str(), chunk.text.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Title", "garden"),
+                ("Count", "1234"),
+                ("Creation Time", "2023-11-14T22:13:20Z"),
+            ],
+            text
+        );
+        assert!(info.utf8_text.is_empty());
+        assert_eq!(Some(png::SrgbRenderingIntent::Perceptual), info.srgb);
+    }
+
+    #[test]
+    fn text_that_isnt_latin1_is_utf8() {
+        let metadata = PngMetadata {
+            name: Some("\u{5EAD}".into()),
+            ..Default::default()
+        };
+        let png = render().to_png_bytes_with_metadata(&metadata).unwrap();
+        let info = decode_info(&png);
+        assert!(info.uncompressed_latin1_text.is_empty());
+        assert_eq!("Title", info.utf8_text[0].keyword);
+        assert_eq!("\u{5EAD}", info.utf8_text[0].get_text().unwrap());
+    }
+
+    #[test]
+    fn no_metadata_by_default() {
+        let render = render();
+        let png = render
+            .to_png_bytes_with_metadata(&PngMetadata::default())
+            .unwrap();
+        assert_eq!(render.to_png_bytes().unwrap(), png);
+        let info = decode_info(&png);
+        assert!(info.uncompressed_latin1_text.is_empty());
+        assert_eq!(None, info.srgb);
+    }
+
+    #[test]
+    fn format_rfc3339() {
+        let at = |seconds| rfc3339(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!("1970-01-01T00:00:00Z", at(0));
+        assert_eq!("2000-02-29T00:00:00Z", at(951_782_400));
+        assert_eq!("2000-03-01T00:00:00Z", at(951_868_800));
+        assert_eq!("2023-11-14T22:13:20Z", at(1_700_000_000));
+        assert_eq!("2038-01-19T03:14:08Z", at(1 << 31));
+    }
+}
@@ -0,0 +1,214 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Palette detection for writing palette-indexed images.
+
+use super::Render;
+use std::mem::size_of;
+
+/// The distinct colors of a render, which is small enough to be written as a palette.
+#[derive(Default)]
+pub(super) struct Palette {
+    /// Colors as RGBA bytes, with every translucent color before the opaque colors
+    /// so that the transparency chunk can leave out the opaque ones.
+    colors: Vec<[u8; 4]>,
+}
+
+impl Palette {
+    /// The most colors that fit in a palette.
+    const MAX_COLORS: usize = 256;
+
+    /// Detect the palette of a render, or return `None` if it has too many colors.
+    pub(super) fn from_render(render: &Render) -> Option<Self> {
+        let mut palette = Self::default();
+        palette.detect(render).then_some(palette)
+    }
+
+    /// Replace this palette with the palette of a render, reusing its memory.
+    ///
+    /// Returns false if the render has too many colors, in which case the palette is incomplete.
+    pub(super) fn detect(&mut self, render: &Render) -> bool {
+        let colors = &mut self.colors;
+        colors.clear();
+        let mut last: &[u8] = &[];
+        for pixel in render.pixels.chunks_exact(size_of::<u32>()) {
+            if pixel != last && !colors.iter().any(|color| color == pixel) {
+                if colors.len() == Self::MAX_COLORS {
+                    return false;
+                }
+                colors.push([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            }
+            last = pixel;
+        }
+        // Stable sort so that the colors otherwise stay in order of appearance.
+        colors.sort_by_key(|color| color[3] == 0xFF);
+        true
+    }
+
+    /// Returns the smallest bit depth that can index every color.
+    pub(super) fn bit_depth(&self) -> png::BitDepth {
+        match self.colors.len() {
+            0..=2 => png::BitDepth::One,
+            3..=4 => png::BitDepth::Two,
+            5..=16 => png::BitDepth::Four,
+            _ => png::BitDepth::Eight,
+        }
+    }
+
+    /// Returns the RGB bytes of every color for a PNG `PLTE` chunk.
+    pub(super) fn rgb(&self) -> Vec<u8> {
+        let mut rgb = Vec::new();
+        self.rgb_into(&mut rgb);
+        rgb
+    }
+
+    /// Replace the contents of `rgb` with the RGB bytes of every color.
+    pub(super) fn rgb_into(&self, rgb: &mut Vec<u8>) {
+        rgb.clear();
+        rgb.extend(self.colors.iter().flat_map(|color| &color[..3]));
+    }
+
+    /// Returns the alpha of every translucent color for a PNG `tRNS` chunk,
+    /// or `None` if every color is opaque.
+    pub(super) fn alpha(&self) -> Option<Vec<u8>> {
+        let mut alpha = Vec::new();
+        self.alpha_into(&mut alpha);
+        (!alpha.is_empty()).then_some(alpha)
+    }
+
+    /// Replace the contents of `alpha` with the alpha of every translucent color,
+    /// which leaves it empty if every color is opaque.
+    pub(super) fn alpha_into(&self, alpha: &mut Vec<u8>) {
+        alpha.clear();
+        alpha.extend(
+            self.colors
+                .iter()
+                .map(|color| color[3])
+                .take_while(|&alpha| alpha != 0xFF),
+        );
+    }
+
+    /// Convert a render's pixels into palette indices, packed into rows at the palette's bit depth.
+    ///
+    /// Every row starts on a byte boundary, and pixels are packed from the most significant bit.
+    /// The indices replace the contents of `indexed`.
+    pub(super) fn index_into(&self, render: &Render, indexed: &mut Vec<u8>) {
+        self.index_at_depth(render, self.bit_depth() as usize, indexed);
+    }
+
+    /// Convert a render's pixels into palette indices with one byte per pixel.
+    pub(super) fn index_bytes(&self, render: &Render) -> Vec<u8> {
+        let mut indexed = Vec::new();
+        self.index_at_depth(render, 8, &mut indexed);
+        indexed
+    }
+
+    fn index_at_depth(&self, render: &Render, bits: usize, indexed: &mut Vec<u8>) {
+        let pixels_per_byte = 8 / bits;
+        let row_bits = render.width * bits;
+        // Round up to a whole byte.
+        let row_len = row_bits / 8 + usize::from(row_bits & 7 != 0);
+        indexed.clear();
+        indexed.resize(row_len * render.height, 0);
+        let rows = render.pixels.chunks_exact(render.width * size_of::<u32>());
+        for (row, indexed_row) in rows.zip(indexed.chunks_exact_mut(row_len)) {
+            // Counters are mostly long runs of the same color, so remember the last lookup.
+            let mut last: Option<(&[u8], u8)> = None;
+            for (x, pixel) in row.chunks_exact(size_of::<u32>()).enumerate() {
+                let index = match last {
+                    Some((last_pixel, index)) if last_pixel == pixel => index,
+                    _ => self
+                        .colors
+                        .iter()
+                        .position(|color| color == pixel)
+                        .expect("every color is in the palette") as u8,
+                };
+                last = Some((pixel, index));
+                let shift = 8 - bits * (x % pixels_per_byte + 1);
+                indexed_row[x / pixels_per_byte] |= index << shift;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(width: usize, colors: &[[u8; 4]]) -> Render {
+        Render {
+            width,
+            height: colors.len() / width,
+            pixels: colors.iter().flatten().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn translucent_colors_come_first() {
+        const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+        const CLEAR: [u8; 4] = [0, 0, 0, 0];
+        const HALF: [u8; 4] = [0x10, 0x20, 0x30, 0x80];
+        let palette = Palette::from_render(&render(2, &[WHITE, CLEAR, HALF, WHITE])).unwrap();
+        assert_eq!(vec![CLEAR, HALF, WHITE], palette.colors);
+        assert_eq!(png::BitDepth::Two, palette.bit_depth());
+        assert_eq!(
+            vec![0, 0, 0, 0x10, 0x20, 0x30, 0xFF, 0xFF, 0xFF],
+            palette.rgb()
+        );
+        assert_eq!(Some(vec![0, 0x80]), palette.alpha());
+        // Rows are `2, 0` and `1, 2`, which are padded to a byte each.
+        let mut indexed = Vec::new();
+        palette.index_into(&render(2, &[WHITE, CLEAR, HALF, WHITE]), &mut indexed);
+        assert_eq!(vec![0b1000_0000, 0b0110_0000], indexed);
+        assert_eq!(
+            vec![2, 0, 1, 2],
+            palette.index_bytes(&render(2, &[WHITE, CLEAR, HALF, WHITE]))
+        );
+    }
+
+    #[test]
+    fn opaque_palette_has_no_alpha() {
+        let palette = Palette::from_render(&render(1, &[[0, 0, 0, 0xFF]])).unwrap();
+        assert_eq!(png::BitDepth::One, palette.bit_depth());
+        assert_eq!(None, palette.alpha());
+    }
+
+    #[test]
+    fn too_many_colors() {
+        let colors: Vec<[u8; 4]> = (0..=256u32)
+            .map(|i| [i as u8, (i >> 8) as u8, 0, 0xFF])
+            .collect();
+        assert!(Palette::from_render(&render(1, &colors)).is_none());
+        assert!(Palette::from_render(&render(1, &colors[..256])).is_some());
+    }
+
+    #[test]
+    fn reused_palette_forgets_the_previous_render() {
+        let colors: Vec<[u8; 4]> = (0..=256u32)
+            .map(|i| [i as u8, (i >> 8) as u8, 0, 0xFF])
+            .collect();
+        let black = render(1, &[[0, 0, 0, 0xFF]]);
+        let mut palette = Palette::default();
+        assert!(!palette.detect(&render(1, &colors)));
+        assert!(palette.detect(&black));
+        assert_eq!(vec![[0, 0, 0, 0xFF]], palette.colors);
+
+        let mut indexed = vec![0xFF; 16];
+        palette.index_into(&black, &mut indexed);
+        assert_eq!(vec![0], indexed);
+    }
+}
@@ -0,0 +1,273 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Image format selection through content negotiation.
+
+use crate::counter::{Render, RenderStyle};
+use std::{error::Error as StdError, fmt, str::FromStr};
+
+/// An image format that a counter can be encoded in.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ImageFormat {
+    /// Palette-indexed PNG, or an animated PNG for odometer animations.
+    #[default]
+    Png,
+    /// Lossless WebP.
+    WebP,
+    /// SVG, with a rectangle per run of pixels.
+    Svg,
+    /// GIF with at most one transparent color.
+    Gif,
+}
+
+/// An error parsing an [`ImageFormat`] from a string.
+#[derive(Debug)]
+pub struct ParseImageFormatError(String);
+
+impl StdError for ParseImageFormatError {}
+
+impl fmt::Display for ParseImageFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown image format `{}` (expected one of: png, webp, svg, gif)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = ParseImageFormatError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "png" => Ok(ImageFormat::Png),
+            "webp" => Ok(ImageFormat::WebP),
+            "svg" => Ok(ImageFormat::Svg),
+            "gif" => Ok(ImageFormat::Gif),
+            _ => Err(ParseImageFormatError(value.into())),
+        }
+    }
+}
+
+impl ImageFormat {
+    /// Every format in order of preference when a client accepts several of them equally.
+    ///
+    /// PNG comes first since it's what the counter has always served, and browsers
+    /// list the others alongside `image/*` in their `Accept` headers for images.
+    pub const ALL: [ImageFormat; 4] = [
+        ImageFormat::Png,
+        ImageFormat::WebP,
+        ImageFormat::Svg,
+        ImageFormat::Gif,
+    ];
+
+    /// Returns the media type for the `Content-Type` header.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::WebP => "image/webp",
+            ImageFormat::Svg => "image/svg+xml",
+            ImageFormat::Gif => "image/gif",
+        }
+    }
+
+    /// Choose the format to respond with from the value of an `Accept` header.
+    ///
+    /// Each format gets the quality of the most specific media range that matches it, and the
+    /// format with the highest quality wins, with ties going to the earlier format in [`ALL`](Self::ALL).
+    /// Clients that don't send the header or don't accept any of the formats get a PNG,
+    /// since an image that might not display is better than no image.
+    pub fn negotiate(accept: Option<&str>) -> ImageFormat {
+        let ranges: Vec<MediaRange<'_>> = accept
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(MediaRange::parse)
+            .collect();
+        let mut best = (0.0, ImageFormat::default());
+        for format in ImageFormat::ALL {
+            let quality = ranges
+                .iter()
+                .filter_map(|range| range.specificity(format.content_type()).map(|s| (s, range)))
+                .max_by_key(|(specificity, _)| *specificity)
+                .map(|(_, range)| range.quality)
+                .unwrap_or(0.0);
+            if quality > best.0 {
+                best = (quality, format);
+            }
+        }
+        best.1
+    }
+
+    /// Encode a render in this format.
+    pub fn encode(&self, render: &Render) -> Result<Vec<u8>, Box<dyn StdError + Send + Sync>> {
+        Ok(match self {
+            ImageFormat::Png => render.to_png_bytes()?,
+            ImageFormat::WebP => render.to_webp_bytes()?,
+            ImageFormat::Svg => render.to_svg_string().into_bytes(),
+            ImageFormat::Gif => render.to_gif_bytes()?,
+        })
+    }
+
+    /// Render an image in a style with `render`, and encode it in this format.
+    ///
+    /// SVG images of styles with [dark colors](RenderStyle::dark_colors) are rendered again in
+    /// the dark colors, which they switch to when the reader prefers a dark color scheme.
+    pub fn render_and_encode(
+        &self,
+        style: &RenderStyle,
+        render: impl Fn(&RenderStyle) -> Render,
+    ) -> Result<Vec<u8>, Box<dyn StdError + Send + Sync>> {
+        let light = render(style);
+        match (self, style.dark_style()) {
+            (ImageFormat::Svg, Some(dark)) => {
+                Ok(light.to_adaptive_svg_string(&render(&dark)).into_bytes())
+            }
+            _ => self.encode(&light),
+        }
+    }
+}
+
+/// A single media range of an `Accept` header, such as `image/*;q=0.8`.
+struct MediaRange<'a> {
+    type_: &'a str,
+    subtype: &'a str,
+    quality: f32,
+}
+
+impl<'a> MediaRange<'a> {
+    fn parse(value: &'a str) -> Option<Self> {
+        let mut params = value.split(';');
+        let (type_, subtype) = params.next()?.trim().split_once('/')?;
+        // Malformed quality values are ignored rather than rejecting the whole header.
+        let quality = params
+            .filter_map(|param| param.trim().split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+            .and_then(|(_, value)| value.trim().parse::<f32>().ok())
+            .map(|quality| quality.clamp(0.0, 1.0))
+            .unwrap_or(1.0);
+        Some(Self {
+            type_: type_.trim(),
+            subtype: subtype.trim(),
+            quality,
+        })
+    }
+
+    /// Returns how specifically this range matches a media type, from 0 for `*/*`
+    /// to 2 for an exact match, or `None` if it doesn't match.
+    fn specificity(&self, media_type: &str) -> Option<u8> {
+        let (type_, subtype) = media_type.split_once('/')?;
+        if self.type_ == "*" && self.subtype == "*" {
+            Some(0)
+        } else if !self.type_.eq_ignore_ascii_case(type_) {
+            None
+        } else if self.subtype == "*" {
+            Some(1)
+        } else if self.subtype.eq_ignore_ascii_case(subtype) {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::{render_separated_number, Color, DarkColors};
+
+    #[test]
+    fn parse_image_format() {
+        assert_eq!(ImageFormat::Png, "png".parse().unwrap());
+        assert_eq!(ImageFormat::WebP, "webp".parse().unwrap());
+        assert_eq!(ImageFormat::Svg, "svg".parse().unwrap());
+        assert_eq!(ImageFormat::Gif, "gif".parse().unwrap());
+        assert!("jpeg".parse::<ImageFormat>().is_err());
+    }
+
+    #[test]
+    fn browsers_get_png() {
+        for accept in [
+            // Chrome
+            "image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8",
+            // Firefox
+            "image/avif,image/webp,image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5",
+            // Safari
+            "image/webp,image/avif,image/jxl,image/heic,image/heic-sequence,video/*;q=0.8,\
+             image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5",
+        ] {
+            assert_eq!(ImageFormat::Png, ImageFormat::negotiate(Some(accept)));
+        }
+    }
+
+    #[test]
+    fn negotiate_by_quality() {
+        assert_eq!(
+            ImageFormat::WebP,
+            ImageFormat::negotiate(Some("image/webp,image/*;q=0.5"))
+        );
+        assert_eq!(
+            ImageFormat::Svg,
+            ImageFormat::negotiate(Some("image/svg+xml"))
+        );
+        assert_eq!(
+            ImageFormat::Gif,
+            ImageFormat::negotiate(Some("image/png;q=0.1, IMAGE/GIF ;q=0.9"))
+        );
+        // An exact match takes precedence over a wildcard, even if it's a refusal.
+        assert_eq!(
+            ImageFormat::WebP,
+            ImageFormat::negotiate(Some("image/*,image/png;q=0"))
+        );
+    }
+
+    #[test]
+    fn negotiate_falls_back_to_png() {
+        assert_eq!(ImageFormat::Png, ImageFormat::negotiate(None));
+        assert_eq!(ImageFormat::Png, ImageFormat::negotiate(Some("")));
+        assert_eq!(ImageFormat::Png, ImageFormat::negotiate(Some("text/html")));
+        assert_eq!(ImageFormat::Png, ImageFormat::negotiate(Some("*/*;q=0")));
+        assert_eq!(
+            ImageFormat::Png,
+            ImageFormat::negotiate(Some("garbage, image/png;q=oops"))
+        );
+    }
+
+    #[test]
+    fn only_svg_adapts_to_dark_colors() {
+        let style = RenderStyle {
+            dark_colors: Some(DarkColors {
+                foreground: Some(Color::BLACK),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let render = |style: &RenderStyle| render_separated_number(42, 5, style);
+        let light = render(&style);
+
+        let svg = ImageFormat::Svg.render_and_encode(&style, render).unwrap();
+        let dark = render(&style.dark_style().unwrap());
+        assert_eq!(light.to_adaptive_svg_string(&dark).into_bytes(), svg);
+
+        let png = ImageFormat::Png.render_and_encode(&style, render).unwrap();
+        assert_eq!(light.to_png_bytes().unwrap(), png);
+
+        let plain = RenderStyle::default();
+        let svg = ImageFormat::Svg.render_and_encode(&plain, render).unwrap();
+        assert_eq!(render(&plain).to_svg_string().into_bytes(), svg);
+    }
+}
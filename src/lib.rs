@@ -18,6 +18,10 @@
 //! A visitor counter for digital gardens that runs as an AWS Lambda function
 //! and stores its count and recent visitors in DynamoDB.
 
+pub mod bot_rules;
+pub mod bot_score;
 pub mod counter;
+pub mod image_format;
+pub mod render_cache;
 pub mod request_info;
 pub mod store;
@@ -0,0 +1,263 @@
+// Digital garden visitor counter
+// A simple visitor counter for digital gardens that runs as an AWS Lambda function.
+// Copyright (C) 2023 John DiSanti.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! An in-memory cache of encoded counter images.
+//!
+//! Visitors that were seen recently don't increment the count, so the same image is often
+//! served many times in a row. The cache lives as long as the process, which means it's reused
+//! across warm Lambda invocations.
+
+use crate::{
+    counter::{render_separated_number, RenderStyle},
+    image_format::ImageFormat,
+};
+use lru::LruCache;
+use std::{
+    error::Error as StdError,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Everything that determines the bytes of an encoded counter image.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct RenderKey {
+    count: usize,
+    reserve_width: usize,
+    style: RenderStyle,
+    /// Address of the style's font. Styles compare fonts by name, but a loaded font can have
+    /// the same name as a built-in one. The style keeps the font alive, so the address can't be
+    /// reused by another font while the key is cached.
+    font: usize,
+    format: ImageFormat,
+}
+
+/// Hit and miss counts of a [`RenderCache`] since it was created.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of images that were found in the cache.
+    pub hits: u64,
+    /// Number of images that had to be rendered.
+    pub misses: u64,
+}
+
+/// An encoded image returned by [`RenderCache::get_or_render`].
+#[derive(Clone, Debug)]
+pub struct CachedImage {
+    /// The encoded image.
+    pub bytes: Arc<[u8]>,
+    /// True if the image was found in the cache rather than rendered.
+    pub hit: bool,
+}
+
+/// A least-recently-used cache of images rendered with
+/// [`render_separated_number`] and encoded with [`ImageFormat::render_and_encode`].
+pub struct RenderCache {
+    entries: Mutex<LruCache<RenderKey, Arc<[u8]>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RenderCache {
+    /// Creates an empty cache that holds up to `capacity` images.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the encoded image of a count, rendering and caching it if it isn't cached.
+    pub fn get_or_render(
+        &self,
+        count: usize,
+        reserve_width: usize,
+        style: &RenderStyle,
+        format: ImageFormat,
+    ) -> Result<CachedImage, Box<dyn StdError + Send + Sync>> {
+        let key = RenderKey {
+            count,
+            reserve_width,
+            style: style.clone(),
+            font: Arc::as_ptr(&style.font) as *const () as usize,
+            format,
+        };
+        if let Some(bytes) = self.lock().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(CachedImage {
+                bytes: bytes.clone(),
+                hit: true,
+            });
+        }
+
+        // Render without holding the lock, since rendering is the slow part. Concurrent misses
+        // for the same image render it twice, which is harmless.
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let bytes: Arc<[u8]> = format
+            .render_and_encode(style, |style| {
+                render_separated_number(count, reserve_width, style)
+            })?
+            .into();
+        self.lock().put(key, bytes.clone());
+        Ok(CachedImage { bytes, hit: false })
+    }
+
+    /// Returns the hit and miss counts since the cache was created.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the number of cached images.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if no images are cached.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<RenderKey, Arc<[u8]>>> {
+        // The cache is still consistent if another thread panicked while holding the lock.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::{
+        font::{self, BitmapFont, LoadedFont},
+        Color,
+    };
+
+    fn cache(capacity: usize) -> RenderCache {
+        RenderCache::new(NonZeroUsize::new(capacity).unwrap())
+    }
+
+    #[test]
+    fn hits_return_the_same_image() {
+        let cache = cache(4);
+        let style = RenderStyle::default();
+        let first = cache
+            .get_or_render(42, 5, &style, ImageFormat::Png)
+            .unwrap();
+        assert!(!first.hit);
+        let second = cache
+            .get_or_render(42, 5, &style, ImageFormat::Png)
+            .unwrap();
+        assert!(second.hit);
+        assert!(Arc::ptr_eq(&first.bytes, &second.bytes));
+        assert_eq!(
+            render_separated_number(42, 5, &style)
+                .to_png_bytes()
+                .unwrap(),
+            &*first.bytes
+        );
+        assert_eq!(CacheStats { hits: 1, misses: 1 }, cache.stats());
+    }
+
+    #[test]
+    fn loaded_font_with_a_builtin_name() {
+        let cache = cache(4);
+        // A PSF1 font with 256 solid 8x8 glyphs, which looks nothing like the built-in font.
+        let mut psf = vec![0x36, 0x04, 0, 8];
+        psf.resize(4 + 256 * 8, 0xFF);
+        let loaded: Arc<dyn BitmapFont> =
+            Arc::new(LoadedFont::from_bytes("classic", &psf).unwrap());
+        let builtin = RenderStyle::default();
+        let loaded = RenderStyle {
+            font: loaded,
+            ..Default::default()
+        };
+        assert_eq!(builtin.font.name(), loaded.font.name());
+
+        let builtin_image = cache
+            .get_or_render(42, 5, &builtin, ImageFormat::Png)
+            .unwrap();
+        let loaded_image = cache
+            .get_or_render(42, 5, &loaded, ImageFormat::Png)
+            .unwrap();
+        assert!(!loaded_image.hit);
+        assert_ne!(builtin_image.bytes, loaded_image.bytes);
+        assert!(
+            cache
+                .get_or_render(42, 5, &RenderStyle::default(), ImageFormat::Png)
+                .unwrap()
+                .hit
+        );
+    }
+
+    #[test]
+    fn every_part_of_the_key_matters() {
+        let cache = cache(16);
+        let style = RenderStyle::default();
+        let other_style = RenderStyle {
+            foreground: Color::BLACK,
+            ..Default::default()
+        };
+        let other_font = RenderStyle {
+            font: font::builtin("lcd").unwrap(),
+            ..Default::default()
+        };
+        for (count, width, style, format) in [
+            (42, 5, &style, ImageFormat::Png),
+            (43, 5, &style, ImageFormat::Png),
+            (42, 6, &style, ImageFormat::Png),
+            (42, 5, &other_style, ImageFormat::Png),
+            (42, 5, &other_font, ImageFormat::Png),
+            (42, 5, &style, ImageFormat::Gif),
+        ] {
+            assert!(
+                !cache
+                    .get_or_render(count, width, style, format)
+                    .unwrap()
+                    .hit
+            );
+        }
+        assert_eq!(6, cache.len());
+        assert_eq!(CacheStats { hits: 0, misses: 6 }, cache.stats());
+    }
+
+    #[test]
+    fn least_recently_used_images_are_evicted() {
+        let cache = cache(2);
+        let style = RenderStyle::default();
+        let render = |count| {
+            cache
+                .get_or_render(count, 5, &style, ImageFormat::Png)
+                .unwrap()
+                .hit
+        };
+        assert!(!render(1));
+        assert!(!render(2));
+        assert!(render(1));
+        // 2 is the least recently used, so it makes room for 3.
+        assert!(!render(3));
+        assert!(render(1));
+        assert!(!render(2));
+        assert_eq!(2, cache.len());
+    }
+}
@@ -17,13 +17,17 @@
 
 //! Request information extraction and bot detection.
 
-use isbot::Bots;
+use crate::{
+    bot_rules::{BotRules, BotVerdict},
+    bot_score::BotScore,
+};
 use lambda_http::{request::RequestContext, Request, RequestExt};
-use once_cell::sync::Lazy;
-use std::{error::Error as StdError, fmt};
-
-/// Initialize the bot checker once and reuse it for every request.
-static BOT_CHECKER: Lazy<Bots> = Lazy::new(Bots::default);
+use std::{
+    error::Error as StdError,
+    fmt,
+    net::{IpAddr, Ipv6Addr},
+    str::FromStr,
+};
 
 /// An error extracting request information from the request.
 #[derive(Debug)]
@@ -34,17 +38,22 @@ pub enum RequestInfoError {
     MissingSourceIp,
     /// The request looks like a bot, and thus, we should reject it.
     LooksLikeABot,
+    /// The request's headers scored too high to be from a browser.
+    SuspiciousHeaders(BotScore),
 }
 
 impl StdError for RequestInfoError {}
 
 impl fmt::Display for RequestInfoError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(match self {
-            Self::MissingUserAgent => "request has no user agent",
-            Self::MissingSourceIp => "request has no source IP",
-            Self::LooksLikeABot => "request looks like a bot",
-        })
+        match self {
+            Self::MissingUserAgent => f.write_str("request has no user agent"),
+            Self::MissingSourceIp => f.write_str("request has no source IP"),
+            Self::LooksLikeABot => f.write_str("request looks like a bot"),
+            Self::SuspiciousHeaders(score) => {
+                write!(f, "request headers look like a bot: {score}")
+            }
+        }
     }
 }
 
@@ -62,37 +71,364 @@ impl TryFrom<&Request> for RequestInfo {
 
     /// Try to extract request information from the request, and return
     /// an error for any request that looks like its from a bot.
+    ///
+    /// No proxies are trusted, so the source IP is whichever address connected to the Lambda,
+    /// and bots are only detected with the default bot list.
     fn try_from(value: &Request) -> Result<Self, Self::Error> {
-        let RequestContext::ApiGatewayV2(context) = value
-            .request_context_ref()
-            .expect("missing request context");
-        let user_agent = context
-            .http
-            .user_agent
-            .as_ref()
-            .ok_or(RequestInfoError::MissingUserAgent)?;
-
-        // Reject bots that are identified by the user agent.
-        if BOT_CHECKER.is_bot(user_agent) {
-            return Err(RequestInfoError::LooksLikeABot);
+        RequestInfo::from_request(value, &TrustedProxies::default(), &BotRules::default())
+    }
+}
+
+impl RequestInfo {
+    /// Try to extract request information from the request, and return
+    /// an error for any request that looks like its from a bot.
+    ///
+    /// If the request came through trusted proxies, the source IP is the address that the
+    /// first untrusted hop forwarded the request from, according to the proxies' headers.
+    /// That source IP is what the `bot_rules` IP rules are checked against.
+    pub fn from_request(
+        request: &Request,
+        trusted_proxies: &TrustedProxies,
+        bot_rules: &BotRules,
+    ) -> Result<Self, RequestInfoError> {
+        let (user_agent, peer_ip) = identity(request);
+        let user_agent = user_agent.ok_or(RequestInfoError::MissingUserAgent)?;
+        let source_ip = source_ip(request, peer_ip, trusted_proxies);
+
+        // Reject bots that are identified by the user agent or the source IP, and then
+        // by their headers, unless an allow rule vouches for them.
+        match bot_rules.check(user_agent, source_ip.as_deref()) {
+            verdict if verdict.is_bot() => return Err(RequestInfoError::LooksLikeABot),
+            BotVerdict::Allowed(_) => {}
+            _ => {
+                if let Some(score) = bot_rules.suspicious_headers(request.headers(), user_agent) {
+                    return Err(RequestInfoError::SuspiciousHeaders(score));
+                }
+            }
         }
 
-        let source_ip = context
-            .http
-            .source_ip
-            .as_ref()
-            .ok_or(RequestInfoError::MissingSourceIp)?;
+        let source_ip = source_ip.ok_or(RequestInfoError::MissingSourceIp)?;
         Ok(RequestInfo {
             user_agent: user_agent.into(),
-            source_ip: source_ip.into(),
+            source_ip,
+        })
+    }
+}
+
+/// Returns the user agent and the IP that connected to the Lambda, if the request context has it,
+/// as `(user_agent, peer_ip)`.
+///
+/// API Gateway reports both in the request context. Load balancers, and anything else that
+/// invokes the Lambda without a request context, only pass on the user agent as a header.
+fn identity(request: &Request) -> (Option<&str>, Option<&str>) {
+    let (user_agent, source_ip) = match request.request_context_ref() {
+        Some(RequestContext::ApiGatewayV1(context)) => (
+            context.identity.user_agent.as_deref(),
+            context.identity.source_ip.as_deref(),
+        ),
+        Some(RequestContext::ApiGatewayV2(context)) => (
+            context.http.user_agent.as_deref(),
+            context.http.source_ip.as_deref(),
+        ),
+        Some(RequestContext::WebSocket(context)) => (
+            context.identity.user_agent.as_deref(),
+            context.identity.source_ip.as_deref(),
+        ),
+        Some(RequestContext::Alb(_)) | None => (None, None),
+    };
+    let header_user_agent = || {
+        request
+            .headers()
+            .get("user-agent")
+            .and_then(|value| value.to_str().ok())
+    };
+    (user_agent.or_else(header_user_agent), source_ip)
+}
+
+/// Work out the client's IP from the IP that connected to the Lambda and the forwarding headers.
+///
+/// Without a peer IP from the request context, the last address of `X-Forwarded-For` is used
+/// instead, since that is where load balancers put the address that they received the request
+/// from. Any addresses before it came from the client or other proxies, which could have made
+/// them up, so they are only followed back from trusted proxies, and only in the header that
+/// the trusted proxies write. Clients can send the other header too, so it's ignored.
+fn source_ip(
+    request: &Request,
+    peer_ip: Option<&str>,
+    trusted_proxies: &TrustedProxies,
+) -> Option<String> {
+    let (peer_ip, peer) = match peer_ip {
+        Some(peer_ip) => match peer_ip.parse() {
+            Ok(peer) => (peer_ip, peer),
+            Err(_) => return Some(peer_ip.into()),
+        },
+        None => {
+            let mut x_forwarded_for =
+                forwarded_hops(request, "x-forwarded-for", parse_x_forwarded_for);
+            let peer = x_forwarded_for.pop()??;
+            let hops = match trusted_proxies.header {
+                ForwardingHeader::XForwardedFor => x_forwarded_for,
+                ForwardingHeader::Forwarded => trusted_proxies.hops(request),
+            };
+            return Some(trusted_proxies.client_ip(peer, &hops).to_string());
+        }
+    };
+    let client = trusted_proxies.client_ip(peer, &trusted_proxies.hops(request));
+    // Keep the address exactly as it was reported if it didn't come from a header.
+    Some(if client == peer {
+        peer_ip.into()
+    } else {
+        client.to_string()
+    })
+}
+
+/// Returns every hop of a forwarding header from the first to the last, with `None` for hops
+/// that don't have an IP, such as obfuscated identifiers. A header can be repeated, in which
+/// case the repeats are in order.
+fn forwarded_hops(
+    request: &Request,
+    name: &str,
+    parse: fn(&str) -> Vec<Option<IpAddr>>,
+) -> Vec<Option<IpAddr>> {
+    request
+        .headers()
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(parse)
+        .collect()
+}
+
+/// Parse the hops of an `X-Forwarded-For` header, such as `203.0.113.7, 10.0.0.1`.
+fn parse_x_forwarded_for(value: &str) -> Vec<Option<IpAddr>> {
+    value
+        .split(',')
+        .filter(|hop| !hop.trim().is_empty())
+        .map(parse_node)
+        .collect()
+}
+
+/// Parse the `for` parameter of every element of an RFC 7239 `Forwarded` header,
+/// such as `for=203.0.113.7;proto=https, for="[2001:db8::17]:4711"`.
+fn parse_forwarded(value: &str) -> Vec<Option<IpAddr>> {
+    value
+        .split(',')
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                .and_then(|(_, node)| parse_node(node))
         })
+        .collect()
+}
+
+/// Parse an IP address that may be quoted, or have a port, such as `"[2001:db8::17]:4711"`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        let (ip, _) = rest.split_once(']')?;
+        return ip.parse::<Ipv6Addr>().ok().map(IpAddr::V6);
+    }
+    node.parse().ok().or_else(|| {
+        // Only IPv4 addresses can have a port without brackets.
+        let (ip, _port) = node.split_once(':')?;
+        ip.parse().ok()
+    })
+}
+
+/// A range of IP addresses in CIDR notation, such as `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IpRange {
+    address: IpAddr,
+    prefix_len: u32,
+}
+
+impl IpRange {
+    /// Returns true if the address is in this range.
+    ///
+    /// IPv4 addresses mapped to IPv6, such as `::ffff:10.0.0.1`, are treated as IPv4 addresses.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        let address = match address {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(address),
+            v4 => v4,
+        };
+        let (network, address, bits) = match (self.address, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                (u32::from(network).into(), u32::from(address).into(), 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                (u128::from(network), u128::from(address), 128)
+            }
+            _ => return false,
+        };
+        // Shifting out all 128 bits of an IPv6 address overflows, but every address is in `::/0`.
+        (network ^ address)
+            .checked_shr(bits - self.prefix_len)
+            .unwrap_or(0)
+            == 0
+    }
+}
+
+/// An error parsing an [`IpRange`] or [`TrustedProxies`] from a string.
+#[derive(Debug)]
+pub struct ParseIpRangeError(String);
+
+impl StdError for ParseIpRangeError {}
+
+impl fmt::Display for ParseIpRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid IP range `{}` (expected an address or a CIDR range, such as 10.0.0.0/8)",
+            self.0
+        )
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = ParseIpRangeError;
+
+    /// Parse a CIDR range, or a single address.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let err = || ParseIpRangeError(value.into());
+        let (address, prefix_len) = match value.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (value, None),
+        };
+        let address: IpAddr = address.trim().parse().map_err(|_| err())?;
+        let bits = if address.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.trim().parse().map_err(|_| err())?,
+            None => bits,
+        };
+        if prefix_len > bits {
+            return Err(err());
+        }
+        Ok(IpRange {
+            address,
+            prefix_len,
+        })
+    }
+}
+
+/// The forwarding header that trusted proxies report the address they received a request from in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ForwardingHeader {
+    /// The `X-Forwarded-For` header, which CloudFront and most reverse proxies append to.
+    #[default]
+    XForwardedFor,
+    /// The RFC 7239 `Forwarded` header.
+    Forwarded,
+}
+
+/// An error parsing a [`ForwardingHeader`] from a string.
+#[derive(Debug)]
+pub struct ParseForwardingHeaderError(String);
+
+impl StdError for ParseForwardingHeaderError {}
+
+impl fmt::Display for ParseForwardingHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid forwarding header `{}` (expected x-forwarded-for or forwarded)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for ForwardingHeader {
+    type Err = ParseForwardingHeaderError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "x-forwarded-for" => Ok(Self::XForwardedFor),
+            "forwarded" => Ok(Self::Forwarded),
+            _ => Err(ParseForwardingHeaderError(value.into())),
+        }
+    }
+}
+
+/// The IP ranges of proxies in front of the Lambda, such as CloudFront or a reverse proxy,
+/// which are trusted to report the address they received a request from in a forwarding header.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrustedProxies {
+    ranges: Vec<IpRange>,
+    header: ForwardingHeader,
+}
+
+impl FromStr for TrustedProxies {
+    type Err = ParseIpRangeError;
+
+    /// Parse a comma-delimited list of IP ranges.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .split(',')
+            .filter(|range| !range.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(|ranges| TrustedProxies {
+                ranges,
+                header: ForwardingHeader::default(),
+            })
+    }
+}
+
+impl TrustedProxies {
+    /// Follow hops in `header` instead of `X-Forwarded-For`.
+    pub fn with_header(mut self, header: ForwardingHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Returns true if the address belongs to a trusted proxy.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        self.ranges.iter().any(|range| range.contains(address))
+    }
+
+    /// Returns the hops of the header that the trusted proxies write.
+    fn hops(&self, request: &Request) -> Vec<Option<IpAddr>> {
+        match self.header {
+            ForwardingHeader::XForwardedFor => {
+                forwarded_hops(request, "x-forwarded-for", parse_x_forwarded_for)
+            }
+            ForwardingHeader::Forwarded => forwarded_hops(request, "forwarded", parse_forwarded),
+        }
+    }
+
+    /// Returns the address of the client, given the `peer` that connected to the Lambda and the
+    /// `hops` of a forwarding header from the first to the last.
+    ///
+    /// Starting from the peer, each trusted proxy's hop is followed back to the address that it
+    /// received the request from, until reaching an address that isn't trusted. A hop that
+    /// doesn't have an IP stops at the proxy that reported it.
+    fn client_ip(&self, peer: IpAddr, hops: &[Option<IpAddr>]) -> IpAddr {
+        let mut client = peer;
+        for hop in hops.iter().rev() {
+            match hop {
+                Some(hop) if self.contains(client) => client = *hop,
+                _ => break,
+            }
+        }
+        client
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use lambda_http::aws_lambda_events::apigw::{
-        ApiGatewayV2httpRequestContext, ApiGatewayV2httpRequestContextHttpDescription,
+    use lambda_http::aws_lambda_events::{
+        alb::AlbTargetGroupRequestContext,
+        apigw::{
+            ApiGatewayProxyRequestContext, ApiGatewayRequestIdentity,
+            ApiGatewayV2httpRequestContext, ApiGatewayV2httpRequestContextHttpDescription,
+        },
     };
 
     use super::*;
@@ -141,6 +477,73 @@ mod tests {
         ));
     }
 
+    fn request_with_headers(
+        context: Option<RequestContext>,
+        headers: &[(&str, &str)],
+    ) -> lambda_http::Request {
+        let mut builder = http::Request::builder().method("GET").uri("/some-url");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        if let Some(context) = context {
+            builder = builder.extension(context);
+        }
+        builder.body(lambda_http::Body::Empty).unwrap()
+    }
+
+    #[test]
+    fn rest_api_request() {
+        let context = RequestContext::ApiGatewayV1(ApiGatewayProxyRequestContext {
+            identity: ApiGatewayRequestIdentity {
+                user_agent: Some("foo bar baz".into()),
+                source_ip: Some("127.0.0.1".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let request = request_with_headers(Some(context), &[]);
+        let info = RequestInfo::try_from(&request).unwrap();
+        assert_eq!("foo bar baz", info.user_agent);
+        assert_eq!("127.0.0.1", info.source_ip);
+    }
+
+    #[test]
+    fn load_balancer_request() {
+        let context = RequestContext::Alb(AlbTargetGroupRequestContext::default());
+        let request = request_with_headers(
+            Some(context),
+            &[
+                ("user-agent", "foo bar baz"),
+                ("x-forwarded-for", "10.0.0.1, 127.0.0.1"),
+            ],
+        );
+        let info = RequestInfo::try_from(&request).unwrap();
+        assert_eq!("foo bar baz", info.user_agent);
+        assert_eq!("127.0.0.1", info.source_ip);
+    }
+
+    #[test]
+    fn request_without_context() {
+        let request = request_with_headers(
+            None,
+            &[("user-agent", "foo bar baz"), ("x-forwarded-for", "::1")],
+        );
+        let info = RequestInfo::try_from(&request).unwrap();
+        assert_eq!("foo bar baz", info.user_agent);
+        assert_eq!("::1", info.source_ip);
+
+        let request = request_with_headers(None, &[]);
+        assert!(matches!(
+            RequestInfo::try_from(&request),
+            Err(RequestInfoError::MissingUserAgent)
+        ));
+        let request = request_with_headers(None, &[("user-agent", "foo bar baz")]);
+        assert!(matches!(
+            RequestInfo::try_from(&request),
+            Err(RequestInfoError::MissingSourceIp)
+        ));
+    }
+
     #[test]
     fn bot() {
         let request = request(Some("irbot"), Some("127.0.0.1"));
@@ -149,4 +552,229 @@ mod tests {
             Err(RequestInfoError::LooksLikeABot)
         ));
     }
+
+    #[test]
+    fn bot_rules_check_the_forwarded_ip() {
+        let request = request_with_headers(
+            api_gateway("10.0.0.1"),
+            &[("x-forwarded-for", "198.51.100.7")],
+        );
+        let from_request = |bot_rules: &str| {
+            RequestInfo::from_request(
+                &request,
+                &"10.0.0.0/8".parse().unwrap(),
+                &bot_rules.parse().unwrap(),
+            )
+        };
+        assert!(matches!(
+            from_request("deny-ip=198.51.100.0/24"),
+            Err(RequestInfoError::LooksLikeABot)
+        ));
+        assert!(from_request("deny-ip=10.0.0.0/8").is_ok());
+    }
+
+    #[test]
+    fn suspicious_headers() {
+        let request = request(Some("Mozilla/5.0 Chrome/118.0.0.0"), Some("127.0.0.1"));
+        let from_request = |bot_rules: BotRules| {
+            RequestInfo::from_request(&request, &TrustedProxies::default(), &bot_rules)
+        };
+
+        // Header scoring is off by default.
+        assert!(from_request(BotRules::default()).is_ok());
+
+        let err = match from_request(BotRules::default().with_score_threshold(Some(3))) {
+            Err(err) => err,
+            Ok(_) => panic!("expected the headers to look like a bot"),
+        };
+        assert_eq!(
+            "request headers look like a bot: score 6 (no Accept-Language header, \
+             no Accept-Encoding header, no Accept header, \
+             browser user agent without Sec-Fetch headers)",
+            err.to_string()
+        );
+        assert!(matches!(err, RequestInfoError::SuspiciousHeaders(score) if score.total() == 6));
+
+        assert!(from_request(BotRules::default().with_score_threshold(Some(7))).is_ok());
+        let allowed: BotRules = "allow-ip=127.0.0.1".parse().unwrap();
+        assert!(from_request(allowed.with_score_threshold(Some(3))).is_ok());
+    }
+
+    fn api_gateway(ip: &str) -> Option<RequestContext> {
+        Some(RequestContext::ApiGatewayV2(
+            ApiGatewayV2httpRequestContext {
+                http: ApiGatewayV2httpRequestContextHttpDescription {
+                    user_agent: Some("foo bar baz".into()),
+                    source_ip: Some(ip.into()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+    }
+
+    fn source_ip(request: &lambda_http::Request, trusted_proxies: &str) -> String {
+        RequestInfo::from_request(
+            request,
+            &trusted_proxies.parse().unwrap(),
+            &BotRules::default(),
+        )
+        .unwrap()
+        .source_ip
+    }
+
+    #[test]
+    fn parse_ip_ranges() {
+        let range: IpRange = "10.0.0.0/8".parse().unwrap();
+        assert!(range.contains("10.1.2.3".parse().unwrap()));
+        assert!(range.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!range.contains("11.0.0.0".parse().unwrap()));
+        assert!(!range.contains("::a01:203".parse().unwrap()));
+
+        let range: IpRange = "2001:db8::/32".parse().unwrap();
+        assert!(range.contains("2001:db8:1::1".parse().unwrap()));
+        assert!(!range.contains("2001:db9::1".parse().unwrap()));
+
+        let single: IpRange = "192.0.2.1".parse().unwrap();
+        assert!(single.contains("192.0.2.1".parse().unwrap()));
+        assert!(!single.contains("192.0.2.2".parse().unwrap()));
+
+        let everything: IpRange = "::/0".parse().unwrap();
+        assert!(everything.contains("2001:db8::1".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("10.0.0/8".parse::<IpRange>().is_err());
+        assert!("10.0.0.0/".parse::<IpRange>().is_err());
+        assert_eq!(
+            TrustedProxies {
+                ranges: vec!["10.0.0.0/8".parse().unwrap(), "::1".parse().unwrap()],
+                header: ForwardingHeader::XForwardedFor,
+            },
+            "10.0.0.0/8, ::1,".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_forwarding_headers() {
+        let ip = |ip: &str| Some(ip.parse::<IpAddr>().unwrap());
+        assert_eq!(
+            vec![ip("203.0.113.7"), ip("10.0.0.1"), None],
+            parse_x_forwarded_for("203.0.113.7, 10.0.0.1:8080, unknown")
+        );
+        assert_eq!(
+            vec![ip("192.0.2.60"), ip("2001:db8:cafe::17"), None, None],
+            parse_forwarded(
+                "for=192.0.2.60;proto=http;by=203.0.113.43, \
+                 For=\"[2001:db8:cafe::17]:4711\", for=_hidden, proto=https"
+            )
+        );
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_forwarding_headers() {
+        let request = request_with_headers(
+            api_gateway("198.51.100.1"),
+            &[("x-forwarded-for", "203.0.113.7")],
+        );
+        assert_eq!("198.51.100.1", source_ip(&request, ""));
+        assert_eq!("198.51.100.1", source_ip(&request, "10.0.0.0/8"));
+    }
+
+    #[test]
+    fn trusted_proxies_are_skipped() {
+        // The client went through CloudFront (192.0.2.0/24) and then a reverse proxy (10.0.0.1).
+        let request = request_with_headers(
+            api_gateway("10.0.0.1"),
+            &[("x-forwarded-for", "203.0.113.7, 192.0.2.10")],
+        );
+        assert_eq!("10.0.0.1", source_ip(&request, ""));
+        assert_eq!("192.0.2.10", source_ip(&request, "10.0.0.0/8"));
+        assert_eq!(
+            "203.0.113.7",
+            source_ip(&request, "10.0.0.0/8,192.0.2.0/24")
+        );
+    }
+
+    #[test]
+    fn spoofed_hops_are_not_trusted() {
+        // The client claims to be forwarding for someone else, which is left of its real address.
+        let request = request_with_headers(
+            api_gateway("10.0.0.1"),
+            &[("x-forwarded-for", "1.2.3.4, 203.0.113.7, 192.0.2.10")],
+        );
+        assert_eq!(
+            "203.0.113.7",
+            source_ip(&request, "10.0.0.0/8,192.0.2.0/24")
+        );
+
+        // A client claiming to be a trusted proxy can only pretend to be another client.
+        let request = request_with_headers(
+            api_gateway("10.0.0.1"),
+            &[("x-forwarded-for", "1.2.3.4, 10.0.0.2, 192.0.2.10")],
+        );
+        assert_eq!("1.2.3.4", source_ip(&request, "10.0.0.0/8,192.0.2.0/24"));
+
+        // Hops without an IP stop at the proxy that reported them.
+        let request = request_with_headers(
+            api_gateway("10.0.0.1"),
+            &[("x-forwarded-for", "203.0.113.7, unknown")],
+        );
+        assert_eq!("10.0.0.1", source_ip(&request, "10.0.0.0/8"));
+    }
+
+    #[test]
+    fn client_sent_forwarded_header_is_ignored() {
+        let request = request_with_headers(
+            api_gateway("10.0.0.1"),
+            &[
+                ("x-forwarded-for", "203.0.113.7"),
+                ("forwarded", "for=1.2.3.4"),
+            ],
+        );
+        assert_eq!("203.0.113.7", source_ip(&request, "10.0.0.0/8"));
+    }
+
+    #[test]
+    fn forwarded_header_when_configured() {
+        let request = request_with_headers(
+            api_gateway("10.0.0.1"),
+            &[
+                ("x-forwarded-for", "198.51.100.1"),
+                ("forwarded", "for=203.0.113.7"),
+                ("forwarded", "for=\"[2001:db8::1]:443\""),
+            ],
+        );
+        let source_ip = |trusted_proxies: &str| {
+            let trusted_proxies = trusted_proxies
+                .parse::<TrustedProxies>()
+                .unwrap()
+                .with_header(ForwardingHeader::Forwarded);
+            RequestInfo::from_request(&request, &trusted_proxies, &BotRules::default())
+                .unwrap()
+                .source_ip
+        };
+        assert_eq!("2001:db8::1", source_ip("10.0.0.0/8"));
+        assert_eq!("203.0.113.7", source_ip("10.0.0.0/8,2001:db8::/32"));
+        assert_eq!(
+            ForwardingHeader::XForwardedFor,
+            "X-Forwarded-For".parse().unwrap()
+        );
+        assert_eq!(ForwardingHeader::Forwarded, "forwarded".parse().unwrap());
+        assert!("via".parse::<ForwardingHeader>().is_err());
+    }
+
+    #[test]
+    fn load_balancer_peer_is_the_last_forwarded_hop() {
+        let context = Some(RequestContext::Alb(AlbTargetGroupRequestContext::default()));
+        let request = request_with_headers(
+            context,
+            &[
+                ("user-agent", "foo bar baz"),
+                ("x-forwarded-for", "1.2.3.4, 203.0.113.7, 10.0.0.1"),
+                ("forwarded", "for=1.2.3.4"),
+            ],
+        );
+        assert_eq!("10.0.0.1", source_ip(&request, ""));
+        assert_eq!("203.0.113.7", source_ip(&request, "10.0.0.0/8"));
+    }
 }
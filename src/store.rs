@@ -25,7 +25,8 @@
 //! name, and the item has two attributes: `count` and `value`. The `count`
 //! is just the current counter value, and `value` is a CBOR encoded list
 //! of recent visitors. Only a 32-bit hash of the visitor's IP and user agent,
-//! and the time they were last seen are stored.
+//! and the time they were last seen are stored. The IP can optionally be truncated
+//! to its network before hashing with [`IpTruncation`].
 //!
 //! The 400 KB maximum item size is taken into account, and the recent visitors
 //! list is culled if it starts getting too long. Additionally, visitors that
@@ -50,9 +51,13 @@ use aws_sdk_dynamodb::{
 };
 use md5::{Digest, Md5};
 use std::{
+    error::Error as StdError,
+    fmt,
     future::Future,
     mem::size_of,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
     pin::Pin,
+    str::FromStr,
     time::{Duration, SystemTime},
 };
 
@@ -70,6 +75,9 @@ const TIMESTAMP_OFFSET: u64 = 1_690_000_000;
 /// How long a visitor is kept in the recent visitors list before being pruned.
 const RECENT_CUTOFF: Duration = Duration::from_secs(7200); // 2 hours
 
+/// How recently a visitor needs to have been seen to be counted as online.
+const ONLINE_CUTOFF: Duration = Duration::from_secs(300); // 5 minutes
+
 type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
 
 /// Trait representing the only operations we use in the DynamoDB client.
@@ -185,12 +193,21 @@ impl From<StoredVisitor> for Visitor {
     }
 }
 
-impl From<&RequestInfo> for Visitor {
-    fn from(value: &RequestInfo) -> Self {
+impl Visitor {
+    /// Create a visitor from a request, truncating its source IP before hashing it.
+    pub fn from_request_info(value: &RequestInfo, truncation: IpTruncation) -> Self {
+        // Untruncated IPs are hashed exactly as they came in so that visitors
+        // keep their tags when truncation is off.
+        let truncated = match value.source_ip.parse() {
+            Ok(ip) if truncation != IpTruncation::NONE => Some(truncation.truncate(ip).to_string()),
+            _ => None,
+        };
+        let source_ip = truncated.as_deref().unwrap_or(&value.source_ip);
+
         // Use the first 32-bits of an MD5 hash of the source IP and user agent to
         // roughly track uniqueness without storing any identifying information.
         let mut hasher = Md5::new();
-        hasher.update(&value.source_ip);
+        hasher.update(source_ip);
         hasher.update(&value.user_agent);
         let hash = &hasher.finalize()[0..size_of::<u32>()];
         let tag = u32_from_ne_bytes(hash);
@@ -202,6 +219,109 @@ impl From<&RequestInfo> for Visitor {
     }
 }
 
+impl From<&RequestInfo> for Visitor {
+    fn from(value: &RequestInfo) -> Self {
+        Visitor::from_request_info(value, IpTruncation::NONE)
+    }
+}
+
+/// How many leading bits of a visitor's IP to keep before it's hashed.
+///
+/// Truncating IPs means the stored hashes can't be matched against a single address,
+/// but everyone on the same network with the same user agent becomes one visitor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IpTruncation {
+    /// Prefix length to keep for IPv4 addresses, from 0 to 32.
+    pub v4_prefix_len: u8,
+    /// Prefix length to keep for IPv6 addresses, from 0 to 128.
+    pub v6_prefix_len: u8,
+}
+
+impl IpTruncation {
+    /// Keep whole IPs.
+    pub const NONE: IpTruncation = IpTruncation {
+        v4_prefix_len: 32,
+        v6_prefix_len: 128,
+    };
+
+    /// Keep the /24 network of IPv4 addresses and the /48 network of IPv6 addresses.
+    pub const NETWORK: IpTruncation = IpTruncation {
+        v4_prefix_len: 24,
+        v6_prefix_len: 48,
+    };
+
+    /// Zero the bits of `ip` past the prefix length. IPv4-mapped IPv6 addresses
+    /// are truncated as IPv4 addresses.
+    pub fn truncate(&self, ip: IpAddr) -> IpAddr {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        match ip {
+            IpAddr::V4(v4) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.v4_prefix_len))
+                    .unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(u32::from(v4) & mask))
+            }
+            IpAddr::V6(v6) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.v6_prefix_len))
+                    .unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(u128::from(v6) & mask))
+            }
+        }
+    }
+}
+
+impl Default for IpTruncation {
+    fn default() -> Self {
+        IpTruncation::NONE
+    }
+}
+
+/// An error parsing an [`IpTruncation`] from a string.
+#[derive(Debug)]
+pub struct ParseIpTruncationError(String);
+
+impl StdError for ParseIpTruncationError {}
+
+impl fmt::Display for ParseIpTruncationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid IP truncation `{}` (expected IPv4 and IPv6 prefix lengths, such as 24,48)",
+            self.0
+        )
+    }
+}
+
+impl FromStr for IpTruncation {
+    type Err = ParseIpTruncationError;
+
+    /// Parse comma-delimited IPv4 and IPv6 prefix lengths, or an empty string for no truncation.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.trim().is_empty() {
+            return Ok(IpTruncation::NONE);
+        }
+        let err = || ParseIpTruncationError(value.into());
+        let (v4, v6) = value.split_once(',').ok_or_else(err)?;
+        let parse = |prefix_len: &str, bits: u8| {
+            prefix_len
+                .trim()
+                .trim_start_matches('/')
+                .parse()
+                .ok()
+                .filter(|&prefix_len| prefix_len <= bits)
+                .ok_or_else(err)
+        };
+        Ok(IpTruncation {
+            v4_prefix_len: parse(v4, 32)?,
+            v6_prefix_len: parse(v6, 128)?,
+        })
+    }
+}
+
 /// Stored representation of a count entry. This becomes the value of the
 /// "value" attribute in DynamoDB, and is stored as a CBOR blob.
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -255,6 +375,28 @@ impl From<StoredCountEntry> for CountEntry {
     }
 }
 
+/// The result of recording a visit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VisitCount {
+    /// The count after the visit was recorded.
+    pub count: usize,
+    /// The count before the visit was recorded, which is the same as `count`
+    /// unless the visitor wasn't seen recently.
+    pub previous_count: usize,
+}
+
+/// A count along with how many visitors were seen recently, which is read without recording a visit.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CountStats {
+    /// The current count.
+    pub count: usize,
+    /// Number of unique visitors seen in the last two hours, which is how long
+    /// visitors are remembered for deduplication.
+    pub recent: usize,
+    /// Number of unique visitors seen in the last five minutes.
+    pub online: usize,
+}
+
 /// An abstraction over count storage in DynamoDB.
 #[derive(Clone)]
 pub struct Store {
@@ -431,19 +573,42 @@ impl Store {
             // Sort descending by last seen time.
             count_entry
                 .recent_visitors
-                .sort_by(|left, right| right.last_seen.cmp(&left.last_seen));
+                .sort_by_key(|visitor| std::cmp::Reverse(visitor.last_seen));
             // Cull the oldest by truncating.
             count_entry.recent_visitors.truncate(max_recent);
         }
     }
 
+    /// Return the count and recent visitor figures without recording a visit.
+    ///
+    /// Counters that haven't been visited yet have a count of zero.
+    pub async fn count_stats(&self, name: &str, now: SystemTime) -> Result<CountStats, BoxError> {
+        let count_entry = match self.get_count_entry(name).await? {
+            Some(count_entry) => count_entry,
+            None => return Ok(CountStats::default()),
+        };
+        // Visitors recorded by another invocation with a slightly faster clock count as just seen.
+        let seen_within = |cutoff: Duration| {
+            count_entry
+                .recent_visitors
+                .iter()
+                .filter(|v| now.duration_since(v.last_seen).unwrap_or_default() < cutoff)
+                .count()
+        };
+        Ok(CountStats {
+            count: count_entry.count as usize,
+            recent: seen_within(RECENT_CUTOFF),
+            online: seen_within(ONLINE_CUTOFF),
+        })
+    }
+
     /// Increment the number of visitors (if this visitor is recently unique), and return the count.
     pub async fn maybe_increment_visitors(
         &self,
         visitor: Visitor,
         name: &str,
         now: SystemTime,
-    ) -> Result<usize, BoxError> {
+    ) -> Result<VisitCount, BoxError> {
         // Looping since we're using optimistic locking. There is a chance another simultaneous execution
         // of this Lambda tries to update the row at the same time. If that happens, keep trying until
         // it works, or until we get to max attempts.
@@ -470,7 +635,10 @@ impl Store {
                     .try_put_count_entry(name, initial_count, count_entry)
                     .await?
                 {
-                    return Ok(count_entry.count as usize);
+                    return Ok(VisitCount {
+                        count: count_entry.count as usize,
+                        previous_count: initial_count as usize,
+                    });
                 } else {
                     attempt += 1;
                     continue;
@@ -478,7 +646,10 @@ impl Store {
             } else {
                 // Try to create a new entry in DynamoDB if there was no entry.
                 if self.try_put_new_count_entry(name, visitor).await? {
-                    return Ok(1);
+                    return Ok(VisitCount {
+                        count: 1,
+                        previous_count: 0,
+                    });
                 } else {
                     attempt += 1;
                     continue;
@@ -493,7 +664,7 @@ impl Store {
 fn u32_from_ne_bytes(bytes: &[u8]) -> u32 {
     let mut buf = [0; size_of::<u32>()];
     buf.copy_from_slice(bytes);
-    unsafe { std::mem::transmute(buf) }
+    u32::from_ne_bytes(buf)
 }
 
 #[cfg(test)]
@@ -502,7 +673,7 @@ mod conversion_tests {
 
     fn big_endian() -> bool {
         let x: u32 = 1;
-        let x_bytes: [u8; size_of::<u32>()] = unsafe { std::mem::transmute(x) };
+        let x_bytes: [u8; size_of::<u32>()] = x.to_ne_bytes();
         x_bytes[0] != 1
     }
 
@@ -538,6 +709,97 @@ mod conversion_tests {
         assert_eq!(4102698867, visitor.tag);
     }
 
+    fn truncated_tag(source_ip: &str, truncation: IpTruncation) -> u32 {
+        let info = RequestInfo {
+            user_agent: "test".to_string(),
+            source_ip: source_ip.to_string(),
+        };
+        Visitor::from_request_info(&info, truncation).tag
+    }
+
+    #[test]
+    fn untruncated_ips_keep_their_tags() {
+        assert_eq!(1600273645, truncated_tag("127.0.0.1", IpTruncation::NONE));
+        // Not canonicalized, since that would change the tags of existing IPv6 visitors.
+        assert_eq!(
+            4102698867,
+            Visitor::from_request_info(
+                &RequestInfo {
+                    user_agent: "testv6".to_string(),
+                    source_ip: "0:0:0:0:0:0:0:1".to_string(),
+                },
+                IpTruncation::NONE
+            )
+            .tag
+        );
+    }
+
+    #[test]
+    fn truncated_ips_share_tags_within_their_network() {
+        let truncation = IpTruncation::NETWORK;
+
+        // The trade-off: different visitors on the same network with the same user
+        // agent are counted once, so deduplication undercounts shared networks.
+        let tag = truncated_tag("203.0.113.7", truncation);
+        assert_eq!(tag, truncated_tag("203.0.113.200", truncation));
+        assert_eq!(tag, truncated_tag("203.0.113.0", truncation));
+        assert_eq!(tag, truncated_tag("::ffff:203.0.113.9", truncation));
+        assert_ne!(tag, truncated_tag("203.0.114.7", truncation));
+        // The truncated address doesn't hash the same as the whole one.
+        assert_ne!(tag, truncated_tag("203.0.113.7", IpTruncation::NONE));
+
+        let tag = truncated_tag("2001:db8:1234:5678::1", truncation);
+        assert_eq!(tag, truncated_tag("2001:db8:1234:ffff:abcd::9", truncation));
+        assert_ne!(tag, truncated_tag("2001:db8:1235::1", truncation));
+
+        // Addresses that can't be parsed are hashed as they are.
+        assert_eq!(
+            truncated_tag("unknown", IpTruncation::NONE),
+            truncated_tag("unknown", truncation)
+        );
+    }
+
+    #[test]
+    fn truncate_ip() {
+        let truncate = |truncation: IpTruncation, ip: &str| {
+            truncation.truncate(ip.parse().unwrap()).to_string()
+        };
+        let custom = IpTruncation {
+            v4_prefix_len: 20,
+            v6_prefix_len: 0,
+        };
+        assert_eq!(
+            "198.51.100.0",
+            truncate(IpTruncation::NETWORK, "198.51.100.42")
+        );
+        assert_eq!("198.51.96.0", truncate(custom, "198.51.100.42"));
+        assert_eq!(
+            "2001:db8:1::",
+            truncate(IpTruncation::NETWORK, "2001:db8:1:2::3")
+        );
+        assert_eq!("::", truncate(custom, "2001:db8:1:2::3"));
+        assert_eq!(
+            "198.51.100.42",
+            truncate(IpTruncation::NONE, "198.51.100.42")
+        );
+        let zero = IpTruncation {
+            v4_prefix_len: 0,
+            v6_prefix_len: 128,
+        };
+        assert_eq!("0.0.0.0", truncate(zero, "198.51.100.42"));
+    }
+
+    #[test]
+    fn parse_ip_truncation() {
+        assert_eq!(IpTruncation::NONE, "".parse().unwrap());
+        assert_eq!(IpTruncation::NETWORK, "24,48".parse().unwrap());
+        assert_eq!(IpTruncation::NETWORK, " /24 , /48 ".parse().unwrap());
+        assert_eq!(IpTruncation::NONE, "32,128".parse().unwrap());
+        for invalid in ["24", "33,48", "24,129", "a,48", "24,48,64"] {
+            assert!(invalid.parse::<IpTruncation>().is_err(), "{invalid}");
+        }
+    }
+
     #[test]
     fn visitor_stored_visitor_round_trip() {
         let time = SystemTime::UNIX_EPOCH + Duration::from_secs(TIMESTAMP_OFFSET + 1000);
@@ -745,7 +1007,13 @@ mod store_tests {
             .maybe_increment_visitors(Visitor::new(1, now), "default", now)
             .await
             .unwrap();
-        assert_eq!(1, result);
+        assert_eq!(
+            VisitCount {
+                count: 1,
+                previous_count: 0,
+            },
+            result
+        );
     }
 
     #[tokio::test]
@@ -781,7 +1049,13 @@ mod store_tests {
             .maybe_increment_visitors(Visitor::new(1234, now), "default", now)
             .await
             .unwrap();
-        assert_eq!(1235, result);
+        assert_eq!(
+            VisitCount {
+                count: 1235,
+                previous_count: 1234,
+            },
+            result
+        );
     }
 
     #[tokio::test]
@@ -819,7 +1093,62 @@ mod store_tests {
             )
             .await
             .unwrap();
-        assert_eq!(1234, result);
+        assert_eq!(
+            VisitCount {
+                count: 1234,
+                previous_count: 1234,
+            },
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn count_stats_without_visiting() {
+        let store = fake_dynamo!(
+            get(input) => {
+                assert_get(&input.build().unwrap(), "default");
+                Ok(output(
+                    1234,
+                    vec![
+                        StoredVisitor::new(1, 1000),
+                        StoredVisitor::new(2, 8000),
+                        StoredVisitor::new(3, 8500),
+                        StoredVisitor::new(4, 8600),
+                    ],
+                ))
+            },
+            put(_input, _attempt) => {
+                panic!("reading the stats shouldn't record a visit")
+            },
+        );
+
+        let stats = store
+            .count_stats("default", system_time(8590))
+            .await
+            .unwrap();
+        assert_eq!(
+            CountStats {
+                count: 1234,
+                // The first visitor was seen over two hours ago.
+                recent: 3,
+                // The last visitor's time is slightly ahead of now.
+                online: 2,
+            },
+            stats
+        );
+    }
+
+    #[tokio::test]
+    async fn count_stats_when_not_existing() {
+        let store = fake_dynamo!(
+            get(_input) => { Ok(GetItemOutput::builder().build()) },
+            put(_input, _attempt) => {
+                panic!("reading the stats shouldn't record a visit")
+            },
+        );
+
+        let stats = store.count_stats("new", system_time(1000)).await.unwrap();
+        assert_eq!(CountStats::default(), stats);
     }
 
     #[tokio::test]
@@ -868,7 +1197,13 @@ mod store_tests {
             .maybe_increment_visitors(Visitor::new(1, time), "default", time)
             .await
             .unwrap();
-        assert_eq!(1235, result);
+        assert_eq!(
+            VisitCount {
+                count: 1235,
+                previous_count: 1234,
+            },
+            result
+        );
     }
 
     #[tokio::test]
@@ -915,7 +1250,13 @@ mod store_tests {
             )
             .await
             .unwrap();
-        assert_eq!(1235, result);
+        assert_eq!(
+            VisitCount {
+                count: 1235,
+                previous_count: 1234,
+            },
+            result
+        );
     }
 
     #[test]